@@ -0,0 +1,250 @@
+//! Conversion of spectra, cycles and chromatograms into Arrow [`RecordBatch`]es.
+//!
+//! Peaks are written in long format, one row per peak, with the columns of [`peak_schema`].
+//! Chromatograms use [`chromatogram_schema`], and several extracted ion chromatograms can be
+//! stacked in long format with [`xic_schema`]. [`write_ipc`] writes a batch as an Arrow IPC
+//! file.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    ArrayBuilder, Float32Builder, Float64Builder, StringBuilder, UInt32Builder, UInt64Builder,
+};
+use arrow_array::{ArrayRef, Float32Array, RecordBatch};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+
+use crate::reader::{Cycle, Spectrum};
+
+/// The schema of a long-format peak table
+///
+/// | column | type | description |
+/// |---|---|---|
+/// | `index` | `u64` | The spectrum or cycle index |
+/// | `function` | `u32` | The function the peak was read from, starting from 0 |
+/// | `time` | `f64` | The retention time in minutes |
+/// | `drift_time` | `f64`, nullable | The drift time in milliseconds, if the function has ion mobility |
+/// | `mz` | `f32` | The peak m/z |
+/// | `intensity` | `f32` | The peak intensity |
+pub fn peak_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("index", DataType::UInt64, false),
+        Field::new("function", DataType::UInt32, false),
+        Field::new("time", DataType::Float64, false),
+        Field::new("drift_time", DataType::Float64, true),
+        Field::new("mz", DataType::Float32, false),
+        Field::new("intensity", DataType::Float32, false),
+    ]))
+}
+
+/// The schema of a chromatogram, with `time` in minutes and `intensity` columns
+pub fn chromatogram_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("time", DataType::Float32, false),
+        Field::new("intensity", DataType::Float32, false),
+    ]))
+}
+
+/// The schema of a long-format table of extracted ion chromatograms
+///
+/// | column | type | description |
+/// |---|---|---|
+/// | `name` | `utf8` | The name of the extracted target |
+/// | `mz` | `f32` | The m/z that was extracted |
+/// | `function` | `u32` | The function it was extracted from, starting from 0 |
+/// | `time` | `f32` | The retention time in minutes |
+/// | `intensity` | `f32` | The extracted intensity |
+pub fn xic_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("mz", DataType::Float32, false),
+        Field::new("function", DataType::UInt32, false),
+        Field::new("time", DataType::Float32, false),
+        Field::new("intensity", DataType::Float32, false),
+    ]))
+}
+
+/// Accumulates peaks into the columns of [`peak_schema`]
+#[derive(Debug, Default)]
+pub struct PeakTableBuilder {
+    index: UInt64Builder,
+    function: UInt32Builder,
+    time: Float64Builder,
+    drift_time: Float64Builder,
+    mz: Float32Builder,
+    intensity: Float32Builder,
+}
+
+impl PeakTableBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of peaks added since the last call to [`PeakTableBuilder::finish`]
+    pub fn len(&self) -> usize {
+        self.mz.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn append_peaks(
+        &mut self,
+        index: usize,
+        function: usize,
+        time: f64,
+        drift_time: Option<f64>,
+        mz_array: &[f32],
+        intensity_array: &[f32],
+    ) {
+        let n = mz_array.len().min(intensity_array.len());
+        self.index.append_value_n(index as u64, n);
+        self.function.append_value_n(function as u32, n);
+        self.time.append_value_n(time, n);
+        match drift_time {
+            Some(dt) => self.drift_time.append_value_n(dt, n),
+            None => self.drift_time.append_nulls(n),
+        }
+        self.mz.append_slice(&mz_array[..n]);
+        self.intensity.append_slice(&intensity_array[..n]);
+    }
+
+    /// Add the peaks of a spectrum
+    pub fn append_spectrum(&mut self, spectrum: &Spectrum) {
+        self.append_peaks(
+            spectrum.index,
+            spectrum.identifier.function,
+            spectrum.time,
+            spectrum.drift_time,
+            &spectrum.mz_array,
+            &spectrum.intensity_array,
+        );
+    }
+
+    /// Add the peaks of every drift scan of a cycle
+    pub fn append_cycle(&mut self, cycle: &Cycle) {
+        for scan in cycle.signal.iter() {
+            self.append_peaks(
+                cycle.index,
+                cycle.identifier.function,
+                cycle.time,
+                Some(scan.drift_time),
+                &scan.mz_array,
+                &scan.intensity_array,
+            );
+        }
+    }
+
+    /// Build a [`RecordBatch`] from the peaks added so far, leaving the builder empty
+    pub fn finish(&mut self) -> Result<RecordBatch, ArrowError> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.index.finish()),
+            Arc::new(self.function.finish()),
+            Arc::new(self.time.finish()),
+            Arc::new(self.drift_time.finish()),
+            Arc::new(self.mz.finish()),
+            Arc::new(self.intensity.finish()),
+        ];
+        RecordBatch::try_new(peak_schema(), columns)
+    }
+}
+
+/// Convert spectra into a long-format peak table
+pub fn spectra_to_record_batch<'a>(
+    spectra: impl IntoIterator<Item = &'a Spectrum>,
+) -> Result<RecordBatch, ArrowError> {
+    let mut builder = PeakTableBuilder::new();
+    for spectrum in spectra {
+        builder.append_spectrum(spectrum);
+    }
+    builder.finish()
+}
+
+/// Convert ion mobility cycles into a long-format peak table, with a row for each peak of
+/// each drift scan
+pub fn cycles_to_record_batch<'a>(
+    cycles: impl IntoIterator<Item = &'a Cycle>,
+) -> Result<RecordBatch, ArrowError> {
+    let mut builder = PeakTableBuilder::new();
+    for cycle in cycles {
+        builder.append_cycle(cycle);
+    }
+    builder.finish()
+}
+
+/// Convert a chromatogram's time and intensity arrays into a [`RecordBatch`]
+pub fn chromatogram_to_record_batch(
+    time: &[f32],
+    intensity: &[f32],
+) -> Result<RecordBatch, ArrowError> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Float32Array::from(time.to_vec())),
+        Arc::new(Float32Array::from(intensity.to_vec())),
+    ];
+    RecordBatch::try_new(chromatogram_schema(), columns)
+}
+
+/// Accumulates extracted ion chromatograms into the columns of [`xic_schema`]
+#[derive(Debug, Default)]
+pub struct XicTableBuilder {
+    name: StringBuilder,
+    mz: Float32Builder,
+    function: UInt32Builder,
+    time: Float32Builder,
+    intensity: Float32Builder,
+}
+
+impl XicTableBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of points added since the last call to [`XicTableBuilder::finish`]
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add the points of the chromatogram extracted at `mz` from `function`, labeled `name`
+    pub fn append_chromatogram(
+        &mut self,
+        name: &str,
+        mz: f32,
+        function: usize,
+        time: &[f32],
+        intensity: &[f32],
+    ) {
+        let n = time.len().min(intensity.len());
+        for _ in 0..n {
+            self.name.append_value(name);
+        }
+        self.mz.append_value_n(mz, n);
+        self.function.append_value_n(function as u32, n);
+        self.time.append_slice(&time[..n]);
+        self.intensity.append_slice(&intensity[..n]);
+    }
+
+    /// Build a [`RecordBatch`] from the points added so far, leaving the builder empty
+    pub fn finish(&mut self) -> Result<RecordBatch, ArrowError> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.name.finish()),
+            Arc::new(self.mz.finish()),
+            Arc::new(self.function.finish()),
+            Arc::new(self.time.finish()),
+            Arc::new(self.intensity.finish()),
+        ];
+        RecordBatch::try_new(xic_schema(), columns)
+    }
+}
+
+/// Write `batch` to `writer` as an Arrow IPC file
+pub fn write_ipc<W: Write>(writer: W, batch: &RecordBatch) -> Result<(), ArrowError> {
+    let mut writer = FileWriter::try_new(writer, &batch.schema())?;
+    writer.write(batch)?;
+    writer.finish()
+}
@@ -1,12 +1,14 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::error::Error;
 use std::ffi::{c_char, c_float, c_int, c_uint, c_void, CStr, CString};
 use std::fmt::Display;
 use std::hash::Hash;
+use std::io;
 use std::path::Path;
 use std::{mem, ptr};
 
-use log::trace;
+use log::{debug, trace};
 
 use crate::constants::MassLynxHeaderItem;
 use crate::{
@@ -18,41 +20,177 @@ use crate::{
 };
 
 macro_rules! fficall {
-    ($task:tt) => {
-        #[allow(unused_braces)]
-        let code = unsafe { $task };
+    ({ ffi::$func:ident($($arg:tt)*) }) => {
+        let code = unsafe { ffi::$func($($arg)*) };
         if code != 0 {
-            return Err(Self::mass_lynx_error_for_code(code));
+            return Err(Self::mass_lynx_error_for_operation(code, stringify!($func)));
         }
     };
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MassLynxError {
     pub error_code: i32,
     pub message: String,
     pub extended_message: Option<String>,
+    /// The SDK function that failed, if this error was raised by the SDK
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub operation: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    kind: MassLynxErrorKind,
+    #[cfg_attr(feature = "serde", serde(default))]
+    from_sdk: bool,
 }
 
 impl MassLynxError {
-    pub fn new(error_code: i32, message: String) -> Self {
-        Self { error_code, message, extended_message: None }
+    /// An error raised by this crate, classified as `kind`
+    pub fn new(kind: MassLynxErrorKind, error_code: i32, message: String) -> Self {
+        Self {
+            error_code,
+            message,
+            extended_message: None,
+            operation: None,
+            kind,
+            from_sdk: false,
+        }
     }
 
-    pub fn extended_new(error_code: i32, message: String, extended_message: Option<String>) -> Self {
-        let mut this = Self::new(error_code, message);
+    pub fn extended_new(
+        kind: MassLynxErrorKind,
+        error_code: i32,
+        message: String,
+        extended_message: Option<String>,
+    ) -> Self {
+        let mut this = Self::new(kind, error_code, message);
         this.extended_message = extended_message;
         this
     }
+
+    /// An error reported by the SDK with `error_code`, classified by [`sdk_error_kind`]
+    pub(crate) fn from_sdk(error_code: i32, message: String) -> Self {
+        let mut this = Self::new(sdk_error_kind(error_code), error_code, message);
+        this.from_sdk = true;
+        this
+    }
+}
+
+/// Error codes for failures raised by this crate rather than by the SDK. They only identify
+/// the failure, [`MassLynxError::kind`] does not depend on them.
+pub(crate) const RAW_PATHS_ERROR_CODE: i32 = 9999;
+pub(crate) const CLOSED_READER_ERROR_CODE: i32 = 9998;
+pub(crate) const INDEX_OUT_OF_BOUNDS_ERROR_CODE: i32 = 9997;
+pub(crate) const SKIPPED_LOCKMASS_ERROR_CODE: i32 = 9996;
+pub(crate) const WATCHER_THREAD_ERROR_CODE: i32 = 9995;
+pub(crate) const UNSUPPORTED_ERROR_CODE: i32 = 9994;
+#[cfg(feature = "rayon")]
+pub(crate) const THREAD_POOL_ERROR_CODE: i32 = 9993;
+pub(crate) const PREFETCH_THREAD_ERROR_CODE: i32 = 9992;
+pub(crate) const FUNCTION_OUT_OF_RANGE_ERROR_CODE: i32 = 9991;
+pub(crate) const PATH_NOT_FOUND_ERROR_CODE: i32 = 9990;
+pub(crate) const NOT_A_RAW_DIRECTORY_ERROR_CODE: i32 = 9989;
+pub(crate) const UNREPRESENTABLE_PATH_ERROR_CODE: i32 = 9988;
+pub(crate) const NULL_HANDLE_ERROR_CODE: i32 = 9987;
+pub(crate) const DECODE_ERROR_CODE: i32 = 9986;
+pub(crate) const IO_ERROR_CODE: i32 = 9985;
+pub(crate) const INVALID_SDK_VALUE_ERROR_CODE: i32 = 9984;
+
+/// The SDK's code for a file that could not be found
+const SDK_FILE_NOT_FOUND_ERROR_CODE: i32 = 5;
+/// The SDK's code for a function number that does not exist
+const SDK_FUNCTION_OUT_OF_RANGE_ERROR_CODE: i32 = 14;
+
+/// Descriptions of the SDK error codes whose meaning is known, used when the SDK has no message
+/// for a code. MassLynxRaw does not publish a list of its codes, so this only covers those that
+/// [`sdk_error_kind`] classifies.
+const SDK_ERROR_MESSAGES: &[(i32, &str)] = &[
+    (SDK_FILE_NOT_FOUND_ERROR_CODE, "File not found"),
+    (
+        SDK_FUNCTION_OUT_OF_RANGE_ERROR_CODE,
+        "Function out of range",
+    ),
+];
+
+/// Classify an SDK error code. Codes without a known meaning are [`MassLynxErrorKind::Sdk`].
+fn sdk_error_kind(error_code: i32) -> MassLynxErrorKind {
+    match error_code {
+        SDK_FILE_NOT_FOUND_ERROR_CODE => MassLynxErrorKind::PathNotFound,
+        SDK_FUNCTION_OUT_OF_RANGE_ERROR_CODE => MassLynxErrorKind::FunctionOutOfRange,
+        _ => MassLynxErrorKind::Sdk,
+    }
+}
+
+/// Describe an SDK error code from [`SDK_ERROR_MESSAGES`], or say that the SDK gave no message
+fn fallback_error_message(error_code: i32) -> String {
+    SDK_ERROR_MESSAGES
+        .iter()
+        .find(|(code, _)| *code == error_code)
+        .map(|(_, message)| message.to_string())
+        .unwrap_or_else(|| format!("MassLynxRaw gave no message for error {error_code}"))
+}
+
+/// The error for an SDK call that reported success without creating the handle it was asked for
+fn null_handle_error(operation: &str) -> MassLynxError {
+    let mut error = MassLynxError::new(
+        MassLynxErrorKind::Sdk,
+        NULL_HANDLE_ERROR_CODE,
+        format!("{operation} succeeded but did not create a handle"),
+    );
+    error.operation = Some(operation.to_string());
+    error
+}
+
+/// A broad classification of a [`MassLynxError`], see [`MassLynxError::kind`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MassLynxErrorKind {
+    /// The RAW directory or one of its files does not exist
+    PathNotFound,
+    /// The path exists but is not a RAW directory
+    NotARawDirectory,
+    /// A spectrum, cycle or other index is out of bounds
+    InvalidIndex,
+    /// A function number that the run does not have
+    FunctionOutOfRange,
+    /// The reader was closed with [`MassLynxReader::close`](crate::reader::MassLynxReader::close)
+    ReaderClosed,
+    /// The requested data belongs to the lock mass function, which is being skipped
+    SkippedLockMass,
+    /// The operation does not apply to this run or function, or the path cannot be passed to
+    /// the SDK
+    Unsupported,
+    /// Reading the files of the RAW directory failed outside of the SDK
+    Io,
+    /// A file in the RAW directory was read but its contents could not be decoded. Text from
+    /// the SDK is never a decoding failure, as it falls back to Windows-1252.
+    Decode,
+    /// A background thread failed to start or exited early
+    Thread,
+    /// Any other error reported by the SDK, including a call that did not create its handle or
+    /// returned a value that cannot be valid
+    #[default]
+    Sdk,
+}
+
+impl MassLynxError {
+    /// What went wrong, as set when the error was created
+    pub fn kind(&self) -> MassLynxErrorKind {
+        self.kind
+    }
+
+    /// Whether this error was raised by the SDK rather than by this crate
+    pub fn is_sdk_error(&self) -> bool {
+        self.from_sdk
+    }
 }
 
 impl Display for MassLynxError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "MassLynx Error occurred: ({}) {}",
-            self.error_code, self.message
-        )?;
+        write!(f, "MassLynx Error occurred")?;
+        if let Some(operation) = self.operation.as_ref() {
+            write!(f, " in {operation}")?;
+        }
+        write!(f, ": ({}) {}", self.error_code, self.message)?;
         if let Some(s) = self.extended_message.as_ref() {
             write!(f, "; {s}")?;
         }
@@ -62,31 +200,159 @@ impl Display for MassLynxError {
 
 impl Error for MassLynxError {}
 
+impl From<MassLynxError> for io::Error {
+    fn from(value: MassLynxError) -> Self {
+        let kind = match value.kind() {
+            MassLynxErrorKind::PathNotFound => io::ErrorKind::NotFound,
+            MassLynxErrorKind::NotARawDirectory => io::ErrorKind::InvalidInput,
+            MassLynxErrorKind::InvalidIndex | MassLynxErrorKind::FunctionOutOfRange => {
+                io::ErrorKind::InvalidInput
+            }
+            MassLynxErrorKind::ReaderClosed => io::ErrorKind::NotConnected,
+            MassLynxErrorKind::Decode => io::ErrorKind::InvalidData,
+            MassLynxErrorKind::Unsupported | MassLynxErrorKind::SkippedLockMass => {
+                io::ErrorKind::Unsupported
+            }
+            MassLynxErrorKind::Io | MassLynxErrorKind::Thread | MassLynxErrorKind::Sdk => {
+                io::ErrorKind::Other
+            }
+        };
+        io::Error::new(kind, value)
+    }
+}
+
 pub type MassLynxResult<T> = Result<T, MassLynxError>;
 
+/// Decode text written by the SDK or by MassLynx. It is read as UTF-8 if valid, and otherwise
+/// as Windows-1252, the code page that the degree and micro signs in units and descriptions
+/// are written in.
+pub(crate) fn decode_text(bytes: &[u8]) -> Cow<'_, str> {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => Cow::Borrowed(s),
+        Err(_) => {
+            encoding_rs::WINDOWS_1252
+                .decode_without_bom_handling(bytes)
+                .0
+        }
+    }
+}
+
+/// Encode `path` as the SDK expects it, in the ANSI code page on Windows.
+///
+/// On Windows, a path with characters outside of the code page is replaced by its short
+/// (8.3) form if the volume provides one. Fails if the path still cannot be represented or
+/// contains a nul byte.
+pub(crate) fn path_to_c_string(path: &Path) -> MassLynxResult<CString> {
+    let unrepresentable = |reason: &str| {
+        MassLynxError::new(
+            MassLynxErrorKind::Unsupported,
+            UNREPRESENTABLE_PATH_ERROR_CODE,
+            format!(
+                "The path {} cannot be passed to the SDK: {reason}",
+                path.display()
+            ),
+        )
+    };
+    let bytes = encode_path(path)
+        .ok_or_else(|| unrepresentable("it has characters outside of the system code page"))?;
+    CString::new(bytes).map_err(|_| unrepresentable("it contains a nul byte"))
+}
+
+#[cfg(not(windows))]
+fn encode_path(path: &Path) -> Option<Vec<u8>> {
+    Some(path.as_os_str().as_encoded_bytes().to_vec())
+}
+
+#[cfg(windows)]
+fn encode_path(path: &Path) -> Option<Vec<u8>> {
+    use std::os::windows::ffi::OsStrExt;
+
+    if unsafe { ffi::GetACP() } == ffi::CP_UTF8 {
+        return path.to_str().map(|s| s.as_bytes().to_vec());
+    }
+    let wide: Vec<u16> = path.as_os_str().encode_wide().collect();
+    wide_to_ansi(&wide).or_else(|| wide_to_ansi(&short_path_of(&wide)?))
+}
+
+/// Convert UTF-16 to the ANSI code page, or `None` if any character has no exact equivalent
+#[cfg(windows)]
+fn wide_to_ansi(wide: &[u16]) -> Option<Vec<u8>> {
+    if wide.is_empty() {
+        return Some(Vec::new());
+    }
+    let convert = |buffer: *mut c_char, size: c_int, used_default: &mut c_int| unsafe {
+        ffi::WideCharToMultiByte(
+            ffi::CP_ACP,
+            ffi::WC_NO_BEST_FIT_CHARS,
+            wide.as_ptr(),
+            wide.len() as c_int,
+            buffer,
+            size,
+            ptr::null(),
+            used_default,
+        )
+    };
+    let mut used_default = 0;
+    let size = convert(ptr::null_mut(), 0, &mut used_default);
+    if size <= 0 {
+        return None;
+    }
+    let mut buffer = vec![0u8; size as usize];
+    let written = convert(buffer.as_mut_ptr() as *mut c_char, size, &mut used_default);
+    if written <= 0 || used_default != 0 {
+        return None;
+    }
+    buffer.truncate(written as usize);
+    Some(buffer)
+}
+
+/// Get the short (8.3) form of a UTF-16 path, which only uses ASCII characters
+#[cfg(windows)]
+fn short_path_of(wide: &[u16]) -> Option<Vec<u16>> {
+    let mut long_path = wide.to_vec();
+    long_path.push(0);
+    let size = unsafe { ffi::GetShortPathNameW(long_path.as_ptr(), ptr::null_mut(), 0) };
+    if size == 0 {
+        return None;
+    }
+    let mut buffer = vec![0u16; size as usize];
+    let written = unsafe { ffi::GetShortPathNameW(long_path.as_ptr(), buffer.as_mut_ptr(), size) };
+    if written == 0 || written >= size {
+        return None;
+    }
+    buffer.truncate(written as usize);
+    Some(buffer)
+}
+
 pub trait MassLynxReaderHelper {
     fn mass_lynx_error_for_code(error_code: i32) -> MassLynxError {
         let error_message = ptr::null();
         unsafe { ffi::getErrorMessage(error_code as c_int, &error_message) };
-        let message = Self::to_string(error_message);
-        MassLynxError {
-            error_code,
-            message,
-            extended_message: None
+        let mut message = Self::to_string(error_message);
+        if message.trim().is_empty() {
+            message = fallback_error_message(error_code);
         }
+        MassLynxError::from_sdk(error_code, message)
     }
 
+    /// Like [`mass_lynx_error_for_code`](Self::mass_lynx_error_for_code), recording the SDK
+    /// function that returned `error_code`
+    fn mass_lynx_error_for_operation(error_code: i32, operation: &str) -> MassLynxError {
+        let mut error = Self::mass_lynx_error_for_code(error_code);
+        let name = operation.rsplit("::").next().unwrap_or(operation).trim();
+        error.operation = Some(name.to_string());
+        error
+    }
+
+    /// Copy a string from the SDK, see [`decode_text`] for how it is decoded.
+    ///
     /// Assumes that the memory behind `c_string` is managed by the client or by the driver
     fn to_string(c_string: *const c_char) -> String {
         if c_string.is_null() {
             return String::new();
-        } else {
-            unsafe {
-                let cs = CStr::from_ptr(c_string);
-                let s = cs.to_string_lossy().to_string();
-                return s;
-            }
         }
+        let bytes = unsafe { CStr::from_ptr(c_string) }.to_bytes();
+        decode_text(bytes).into_owned()
     }
 
     /// Assumes that the memory behind `p_array` is managed by the client or by the driver
@@ -140,7 +406,10 @@ macro_rules! get_function_property {
             let mut prop: $prop_type = unsafe { mem::MaybeUninit::zeroed().assume_init() };
             let code = unsafe { ($ffi_fn)(self.0, which_function as c_int, &mut prop) };
             if code != 0 {
-                Err(Self::mass_lynx_error_for_code(code))
+                Err(Self::mass_lynx_error_for_operation(
+                    code,
+                    stringify!($ffi_fn),
+                ))
             } else {
                 Ok(prop)
             }
@@ -152,7 +421,10 @@ macro_rules! get_function_property {
             let prop: $prop_type = unsafe { mem::MaybeUninit::zeroed().assume_init() };
             let code = unsafe { ($ffi_fn)(self.0, which_function as c_int, &prop) };
             if code != 0 {
-                Err(Self::mass_lynx_error_for_code(code))
+                Err(Self::mass_lynx_error_for_operation(
+                    code,
+                    stringify!($ffi_fn),
+                ))
             } else {
                 Ok(prop != 0)
             }
@@ -164,7 +436,10 @@ macro_rules! get_function_property {
             let mut prop: $prop_type = unsafe { mem::MaybeUninit::zeroed().assume_init() };
             let code = unsafe { ($ffi_fn)(self.0, which_function as c_int, &mut prop) };
             if code != 0 {
-                Err(Self::mass_lynx_error_for_code(code))
+                Err(Self::mass_lynx_error_for_operation(
+                    code,
+                    stringify!($ffi_fn),
+                ))
             } else {
                 Ok(prop as $out_type)
             }
@@ -182,7 +457,10 @@ macro_rules! get_function_property_two {
             let mut prop2: $prop_type2 = unsafe { mem::MaybeUninit::zeroed().assume_init() };
             let code = unsafe { ($ffi_fn)(self.0, which_function as c_int, &mut prop, &mut prop2) };
             if code != 0 {
-                Err(Self::mass_lynx_error_for_code(code))
+                Err(Self::mass_lynx_error_for_operation(
+                    code,
+                    stringify!($ffi_fn),
+                ))
             } else {
                 Ok((prop, prop2))
             }
@@ -196,7 +474,10 @@ macro_rules! get_file_reader_property {
             let prop: $prop_type = unsafe { mem::MaybeUninit::zeroed().assume_init() };
             let code = unsafe { ($ffi_fn)(self.0, &prop) };
             if code != 0 {
-                Err(Self::mass_lynx_error_for_code(code))
+                Err(Self::mass_lynx_error_for_operation(
+                    code,
+                    stringify!($ffi_fn),
+                ))
             } else {
                 Ok(prop)
             }
@@ -208,7 +489,10 @@ macro_rules! get_file_reader_property {
             let prop: $prop_type = unsafe { mem::MaybeUninit::zeroed().assume_init() };
             let code = unsafe { ($ffi_fn)(self.0, &prop) };
             if code != 0 {
-                Err(Self::mass_lynx_error_for_code(code))
+                Err(Self::mass_lynx_error_for_operation(
+                    code,
+                    stringify!($ffi_fn),
+                ))
             } else {
                 Ok(prop != 0)
             }
@@ -227,7 +511,10 @@ macro_rules! get_scan_property {
             let code =
                 unsafe { ($ffi_fn)(self.0, which_function as c_int, which_scan as c_int, &prop) };
             if code != 0 {
-                Err(Self::mass_lynx_error_for_code(code))
+                Err(Self::mass_lynx_error_for_operation(
+                    code,
+                    stringify!($ffi_fn),
+                ))
             } else {
                 Ok(prop)
             }
@@ -240,7 +527,10 @@ macro_rules! get_scan_property {
             let code =
                 unsafe { ($ffi_fn)(self.0, which_function as c_int, which_scan as c_int, &prop) };
             if code != 0 {
-                Err(Self::mass_lynx_error_for_code(code))
+                Err(Self::mass_lynx_error_for_operation(
+                    code,
+                    stringify!($ffi_fn),
+                ))
             } else {
                 Ok(prop != 0)
             }
@@ -263,7 +553,10 @@ macro_rules! get_scan_property {
                 )
             };
             if code != 0 {
-                Err(Self::mass_lynx_error_for_code(code))
+                Err(Self::mass_lynx_error_for_operation(
+                    code,
+                    stringify!($ffi_fn),
+                ))
             } else {
                 Ok(prop as $out_type)
             }
@@ -275,10 +568,15 @@ pub struct MassLynxParameters(pub(crate) ffi::CMassLynxParameters);
 
 impl MassLynxParameters {
     pub fn new() -> MassLynxResult<Self> {
-        let this = Self(ptr::null_mut());
-        let code = unsafe { ffi::createParameters(&this.0) };
+        let mut this = Self(ptr::null_mut());
+        let code = unsafe { ffi::createParameters(&mut this.0) };
         if code != 0 {
-            Err(Self::mass_lynx_error_for_code(code))
+            Err(Self::mass_lynx_error_for_operation(
+                code,
+                "createParameters",
+            ))
+        } else if this.0.is_null() {
+            Err(null_handle_error("createParameters"))
         } else {
             Ok(this)
         }
@@ -288,7 +586,10 @@ impl MassLynxParameters {
         let out = ptr::null();
         let code = unsafe { ffi::getParameterValue(self.0, key.as_key(), &out) };
         if code != 0 {
-            Err(Self::mass_lynx_error_for_code(code))
+            Err(Self::mass_lynx_error_for_operation(
+                code,
+                "getParameterValue",
+            ))
         } else {
             Ok(Self::to_string(out))
         }
@@ -300,28 +601,58 @@ impl MassLynxParameters {
         let code = unsafe { ffi::setParameterValue(self.0, key.as_key(), value_ptr.as_ptr()) };
 
         if code != 0 {
-            Err(Self::mass_lynx_error_for_code(code))
+            Err(Self::mass_lynx_error_for_operation(
+                code,
+                "setParameterValue",
+            ))
         } else {
             Ok(())
         }
     }
 
+    /// Set each of `keys` to an empty value, so that a key a later call leaves unset is not
+    /// read back with the value from an earlier one
+    pub fn clear_keys<T: AsMassLynxItemKey>(&mut self, keys: &[T]) -> MassLynxResult<()> {
+        let empty = CString::default();
+        for key in keys {
+            let code = unsafe { ffi::setParameterValue(self.0, key.as_key(), empty.as_ptr()) };
+            if code != 0 {
+                return Err(Self::mass_lynx_error_for_operation(
+                    code,
+                    "setParameterValue",
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_raw_keys(&self) -> MassLynxResult<Vec<c_int>> {
         let keys = ptr::null();
         let size: c_int = 0;
         let code = unsafe { ffi::getParameterKeys(self.0, &keys, &size) };
         if code != 0 {
-            Err(Self::mass_lynx_error_for_code(code))
+            Err(Self::mass_lynx_error_for_operation(
+                code,
+                "getParameterKeys",
+            ))
         } else {
             Ok(Self::to_vec(keys, size))
         }
     }
 
+    /// Get the keys that can be converted to `T`. Unrecognized keys are skipped, use
+    /// [`ItemKey<T>`](crate::constants::ItemKey) as `T` to keep them.
     pub fn get_keys<T: AsMassLynxItemKey>(&self) -> MassLynxResult<Vec<T>> {
         Ok(self
             .get_raw_keys()?
             .into_iter()
-            .flat_map(|k| k.try_into())
+            .filter_map(|k| match k.try_into() {
+                Ok(key) => Some(key),
+                Err(_) => {
+                    debug!("Skipping unrecognized parameter key {k}");
+                    None
+                }
+            })
             .collect())
     }
 
@@ -353,7 +684,9 @@ impl MassLynxReaderHelper for MassLynxParameters {}
 
 impl Drop for MassLynxParameters {
     fn drop(&mut self) {
-        unsafe { ffi::destroyParameters(self.0) };
+        if !self.0.is_null() {
+            unsafe { ffi::destroyParameters(self.0) };
+        }
     }
 }
 
@@ -367,6 +700,9 @@ macro_rules! impl_reader_apis {
 
         impl Drop for $tp {
             fn drop(&mut self) {
+                if self.0.is_null() {
+                    return;
+                }
                 trace!("Destroying Reader {:?}", Self::base_type());
                 unsafe {
                     ffi::destroyRawReader(self.0);
@@ -406,16 +742,14 @@ pub trait AsMassLynxSource: Default + MassLynxReaderHelper {
     fn base_type() -> MassLynxBaseType;
 
     fn from_path<P: AsRef<Path>>(path: P) -> MassLynxResult<Self> {
-        let path = path.as_ref();
-        let path_str = path.as_os_str();
-        let s = path_str.as_encoded_bytes();
-        // Ensure there's a trailing nul byte
-        let s = CString::new(s).expect("Failed to convert path to a C-compatible string");
+        let s = path_to_c_string(path.as_ref())?;
         let mut this = Self::default();
         fficall!({
             ffi::createRawReaderFromPath(s.as_ptr(), this.source_mut(), Self::base_type())
         });
-        debug_assert!(!this.as_mass_lynx_source().is_null());
+        if this.as_mass_lynx_source().is_null() {
+            return Err(null_handle_error("createRawReaderFromPath"));
+        }
         Ok(this)
     }
 
@@ -424,7 +758,9 @@ pub trait AsMassLynxSource: Default + MassLynxReaderHelper {
         let reader_type = Self::base_type();
         let source_ptr = source.as_mass_lynx_source();
         fficall!({ ffi::createRawReaderFromReader(source_ptr, this.source_mut(), reader_type,) });
-        debug_assert!(!this.as_mass_lynx_source().is_null());
+        if this.as_mass_lynx_source().is_null() {
+            return Err(null_handle_error("createRawReaderFromReader"));
+        }
         Ok(this)
     }
 }
@@ -442,7 +778,10 @@ impl MassLynxInfoReader {
         let count: c_uint = 0;
         let code = unsafe { ffi::getFunctionCount(self.0, &count) };
         if code != 0 {
-            Err(Self::mass_lynx_error_for_code(code))
+            Err(Self::mass_lynx_error_for_operation(
+                code,
+                "getFunctionCount",
+            ))
         } else {
             Ok(count as usize)
         }
@@ -452,7 +791,7 @@ impl MassLynxInfoReader {
         let count: c_uint = 0;
         let code = unsafe { ffi::getScanCount(self.0, which_function as c_int, &count) };
         if code != 0 {
-            Err(Self::mass_lynx_error_for_code(code))
+            Err(Self::mass_lynx_error_for_operation(code, "getScanCount"))
         } else {
             Ok(count as usize)
         }
@@ -475,6 +814,25 @@ impl MassLynxInfoReader {
     );
     get_function_property!(get_mrm_count, c_int as usize, ffi::getMRMCount);
 
+    pub fn get_function_type_string(
+        &self,
+        function_type: MassLynxFunctionType,
+    ) -> MassLynxResult<String> {
+        let s = ptr::null();
+
+        fficall!({ ffi::getFunctionTypeString(self.0, function_type, &s) });
+
+        Ok(Self::to_string(s))
+    }
+
+    pub fn get_ion_mode_string(&self, ion_mode: MassLynxIonMode) -> MassLynxResult<String> {
+        let s = ptr::null();
+
+        fficall!({ ffi::getIonModeString(self.0, ion_mode, &s) });
+
+        Ok(Self::to_string(s))
+    }
+
     get_function_property_two!(
         get_acquisition_time_range,
         c_float,
@@ -501,6 +859,52 @@ impl MassLynxInfoReader {
         Ok(out as f64)
     }
 
+    /// Convert a drift time to a collisional cross section for an ion of the given m/z and charge
+    pub fn get_collisional_cross_section(
+        &self,
+        drift_time: f32,
+        mass: f32,
+        charge: i32,
+    ) -> MassLynxResult<f64> {
+        let mut out = 0.0;
+
+        fficall!({
+            ffi::getCollisionalCrossSection(self.0, drift_time, mass, charge as c_int, &mut out)
+        });
+
+        Ok(out as f64)
+    }
+
+    /// Convert a collisional cross section to a drift time for an ion of the given m/z and charge
+    pub fn get_drift_time_for_ccs(&self, ccs: f32, mass: f32, charge: i32) -> MassLynxResult<f64> {
+        let mut out: c_float = 0.0;
+
+        fficall!({ ffi::getDriftTime_CCS(self.0, ccs, mass, charge as c_int, &mut out) });
+
+        Ok(out as f64)
+    }
+
+    /// Convert many `(drift_time, mass, charge)` ions to collisional cross sections, with a
+    /// result for each ion in the same order
+    pub fn get_collisional_cross_sections(
+        &self,
+        ions: &[(f32, f32, i32)],
+    ) -> Vec<MassLynxResult<f64>> {
+        ions.iter()
+            .map(|(drift_time, mass, charge)| {
+                self.get_collisional_cross_section(*drift_time, *mass, *charge)
+            })
+            .collect()
+    }
+
+    /// Convert many `(ccs, mass, charge)` ions to drift times, with a result for each ion in
+    /// the same order
+    pub fn get_drift_times_for_ccs(&self, ions: &[(f32, f32, i32)]) -> Vec<MassLynxResult<f64>> {
+        ions.iter()
+            .map(|(ccs, mass, charge)| self.get_drift_time_for_ccs(*ccs, *mass, *charge))
+            .collect()
+    }
+
     pub fn get_acquisition_mass_range(&self, which_function: usize) -> MassLynxResult<(f64, f64)> {
         let low: c_float = 0.0;
         let high: c_float = 0.0;
@@ -508,7 +912,10 @@ impl MassLynxInfoReader {
             ffi::getAcquisitionMassRange(self.0, which_function as c_int, 0, &low, &high)
         };
         if code != 0 {
-            Err(Self::mass_lynx_error_for_code(code))
+            Err(Self::mass_lynx_error_for_operation(
+                code,
+                "getAcquisitionMassRange",
+            ))
         } else {
             Ok((low as f64, high as f64))
         }
@@ -519,10 +926,21 @@ impl MassLynxInfoReader {
         items: &[MassLynxHeaderItem],
     ) -> MassLynxResult<MassLynxParameters> {
         let params = MassLynxParameters::new()?;
+        self.get_header_items_into(items, &params)?;
+        Ok(params)
+    }
+
+    /// As [`MassLynxInfoReader::get_header_items`], writing into an existing `params` so that
+    /// one handle can be reused. Values set by earlier calls are not removed.
+    pub fn get_header_items_into(
+        &self,
+        items: &[MassLynxHeaderItem],
+        params: &MassLynxParameters,
+    ) -> MassLynxResult<()> {
         fficall!({
             ffi::getHeaderItemValue(self.0, items.as_ptr(), items.len() as c_int, params.0)
         });
-        Ok(params)
+        Ok(())
     }
 
     pub fn get_acquisition_info(&mut self) -> MassLynxResult<MassLynxParameters> {
@@ -539,6 +957,18 @@ impl MassLynxInfoReader {
         Ok(params)
     }
 
+    /// Get the SDK's display names for `items`, keyed by item
+    pub fn get_scan_item_names(
+        &self,
+        items: &[MassLynxScanItem],
+    ) -> MassLynxResult<MassLynxParameters> {
+        let params = MassLynxParameters::new()?;
+
+        fficall!({ ffi::getScanItemName(self.0, items.as_ptr(), items.len() as c_int, params.0) });
+
+        Ok(params)
+    }
+
     pub fn get_scan_item_values_for_scan(
         &self,
         which_function: usize,
@@ -546,7 +976,20 @@ impl MassLynxInfoReader {
         items: &[MassLynxScanItem],
     ) -> MassLynxResult<MassLynxParameters> {
         let params = MassLynxParameters::new()?;
+        self.get_scan_item_values_for_scan_into(which_function, which_scan, items, &params)?;
+        Ok(params)
+    }
 
+    /// As [`MassLynxInfoReader::get_scan_item_values_for_scan`], writing into an existing
+    /// `params` so that one handle can be reused. Values set by earlier calls are not removed,
+    /// see [`MassLynxParameters::clear_keys`].
+    pub fn get_scan_item_values_for_scan_into(
+        &self,
+        which_function: usize,
+        which_scan: usize,
+        items: &[MassLynxScanItem],
+        params: &MassLynxParameters,
+    ) -> MassLynxResult<()> {
         fficall!({
             ffi::getScanItemValue(
                 self.0,
@@ -558,7 +1001,7 @@ impl MassLynxInfoReader {
             )
         });
 
-        Ok(params)
+        Ok(())
     }
 }
 
@@ -817,17 +1260,22 @@ pub struct MassLynxLockMassProcessor(ffi::CMassLynxBaseProcessor);
 
 impl MassLynxLockMassProcessor {
     pub fn new() -> MassLynxResult<Self> {
-        let this = Self::default();
+        let mut this = Self::default();
         let code = unsafe {
             ffi::createRawProcessor(
-                &this.0,
+                &mut this.0,
                 MassLynxBaseType::LOCKMASS,
                 None,
                 ptr::addr_of!(this) as *const c_void,
             )
         };
         if code != 0 {
-            Err(Self::mass_lynx_error_for_code(code))
+            Err(Self::mass_lynx_error_for_operation(
+                code,
+                "createRawProcessor",
+            ))
+        } else if this.0.is_null() {
+            Err(null_handle_error("createRawProcessor"))
         } else {
             Ok(this)
         }
@@ -843,7 +1291,7 @@ impl MassLynxLockMassProcessor {
     }
 
     pub fn set_raw_data_from_path(&mut self, path: String) -> MassLynxResult<()> {
-        let cpath = CString::new(path).expect("Failed to convert path to C-compatible string");
+        let cpath = path_to_c_string(Path::new(&path))?;
         fficall!({ ffi::setRawPath(self.0, cpath.as_ptr() as *const i8) });
         Ok(())
     }
@@ -884,6 +1332,14 @@ impl MassLynxLockMassProcessor {
         Ok(corrected != 0)
     }
 
+    /// Correct using the reference settings recorded by the acquisition method. If `force`
+    /// is set, correct even if the run is already corrected.
+    pub fn auto_lock_mass_correct(&mut self, force: bool) -> MassLynxResult<bool> {
+        let mut corrected: c_char = 0;
+        fficall!({ ffi::autoLockMassCorrect(self.0, force as c_char, &mut corrected) });
+        Ok(corrected != 0)
+    }
+
     pub fn get_candidates(
         &mut self,
         masses: &mut Vec<f32>,
@@ -907,8 +1363,10 @@ impl MassLynxReaderHelper for MassLynxLockMassProcessor {}
 
 impl Drop for MassLynxLockMassProcessor {
     fn drop(&mut self) {
-        unsafe {
-            ffi::destroyRawProcessor(self.0);
+        if !self.0.is_null() {
+            unsafe {
+                ffi::destroyRawProcessor(self.0);
+            }
         }
     }
 }
@@ -972,17 +1430,22 @@ pub struct MassLynxScanProcessor(ffi::CMassLynxBaseProcessor);
 
 impl MassLynxScanProcessor {
     pub fn new() -> MassLynxResult<Self> {
-        let this = Self::default();
+        let mut this = Self::default();
         let code = unsafe {
             ffi::createRawProcessor(
-                &this.0,
+                &mut this.0,
                 MassLynxBaseType::SCAN,
                 None,
                 ptr::addr_of!(this) as *const c_void,
             )
         };
         if code != 0 {
-            Err(Self::mass_lynx_error_for_code(code))
+            Err(Self::mass_lynx_error_for_operation(
+                code,
+                "createRawProcessor",
+            ))
+        } else if this.0.is_null() {
+            Err(null_handle_error("createRawProcessor"))
         } else {
             Ok(this)
         }
@@ -1125,8 +1588,10 @@ impl MassLynxReaderHelper for MassLynxScanProcessor {}
 
 impl Drop for MassLynxScanProcessor {
     fn drop(&mut self) {
-        unsafe {
-            ffi::destroyRawProcessor(self.0);
+        if !self.0.is_null() {
+            unsafe {
+                ffi::destroyRawProcessor(self.0);
+            }
         }
     }
 }
@@ -1136,3 +1601,23 @@ impl Default for MassLynxScanProcessor {
         Self(ptr::null_mut())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kind_does_not_depend_on_code() {
+        let sdk = MassLynxError::from_sdk(RAW_PATHS_ERROR_CODE, "From the SDK".to_string());
+        assert_eq!(sdk.kind(), MassLynxErrorKind::Sdk);
+        assert!(sdk.is_sdk_error());
+
+        let not_found = MassLynxError::from_sdk(SDK_FILE_NOT_FOUND_ERROR_CODE, String::new());
+        assert_eq!(not_found.kind(), MassLynxErrorKind::PathNotFound);
+
+        let ours = MassLynxError::new(MassLynxErrorKind::Io, 1, "Not from the SDK".to_string());
+        assert_eq!(ours.kind(), MassLynxErrorKind::Io);
+        assert!(!ours.is_sdk_error());
+        assert_eq!(io::Error::from(ours).kind(), io::ErrorKind::Other);
+    }
+}
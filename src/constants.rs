@@ -1,6 +1,7 @@
 #![allow(unused, non_camel_case_types)]
 
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
 use std::ffi::c_int;
 
 const TYPE_BASE: u32 = 1;
@@ -55,8 +56,105 @@ macro_rules! impl_as_key {
     };
 }
 
+/// A parameter key that may not be known to this crate.
+///
+/// Newer SDK versions report keys outside the ranges the enums here cover. Reading
+/// [`MassLynxParameters`](crate::MassLynxParameters) with `ItemKey<T>` as the key type keeps
+/// those entries as [`ItemKey::Unknown`] with their raw code, where reading with `T` drops them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ItemKey<T> {
+    Known(T),
+    Unknown(i32),
+}
+
+impl<T: AsMassLynxItemKey> ItemKey<T> {
+    pub fn known(&self) -> Option<T> {
+        match self {
+            Self::Known(key) => Some(*key),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// The raw key code
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::Known(key) => key.as_key(),
+            Self::Unknown(code) => *code,
+        }
+    }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+impl<T: AsMassLynxItemKey> From<i32> for ItemKey<T> {
+    fn from(value: i32) -> Self {
+        match T::try_from(value) {
+            Ok(key) => Self::Known(key),
+            Err(_) => Self::Unknown(value),
+        }
+    }
+}
+
+impl<T: AsMassLynxItemKey> AsMassLynxItemKey for ItemKey<T> {
+    fn as_key(&self) -> c_int {
+        self.code()
+    }
+}
+
+/// Known keys are written as their variant name, unknown keys as `UNKNOWN_<code>`
+impl<T: AsMassLynxItemKey> Display for ItemKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Known(key) => write!(f, "{key:?}"),
+            Self::Unknown(code) => write!(f, "UNKNOWN_{code}"),
+        }
+    }
+}
+
+/// Define a constant enum along with its `TryFrom<i32>` conversion, its [`AsMassLynxItemKey`]
+/// implementation, and an `ALL` slice and `iter()` over its variants in declaration order.
+macro_rules! masslynx_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$vmeta:meta])* $variant:ident = $value:expr),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$vmeta])* $variant = $value,)+
+        }
+
+        impl TryFrom<i32> for $name {
+            type Error = String;
+
+            fn try_from(value: i32) -> Result<Self, Self::Error> {
+                $(
+                    if value as u32 == Self::$variant as u32 {
+                        return Ok(Self::$variant)
+                    }
+                )+
+                Err(format!("Cannot convert {value} into {}", stringify!($name)))
+            }
+        }
+
+        impl_as_key!($name);
+
+        impl $name {
+            /// Every variant, in declaration order
+            pub const ALL: &'static [Self] = &[$(Self::$variant,)+];
+
+            /// Iterate over every variant, in declaration order
+            pub fn iter() -> impl Iterator<Item = Self> {
+                Self::ALL.iter().copied()
+            }
+        }
+    };
+}
+
+
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum MassLynxBaseType {
     SCAN = TYPE_BASE,
@@ -68,8 +166,11 @@ pub enum MassLynxBaseType {
     DDA = TYPE_BASE + 6,
     MSE = TYPE_BASE + 7,
 }
+}
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+masslynx_enum! {
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum MassLynxIonMode {
     EI_POS = ION_MODE_BASE,
@@ -89,8 +190,11 @@ pub enum MassLynxIonMode {
     #[default]
     UNINITIALISED = ION_MODE_BASE + 99,
 }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum MassLynxFunctionType { // ProteoWizard classifications
     /// FunctionType_Scan, |  Standard MS scanning function
@@ -159,8 +263,11 @@ pub enum MassLynxFunctionType { // ProteoWizard classifications
     ASMRMQ = 31 + FUNCTION_TYPE_BASE,
     UNINITIALISED = FUNCTION_TYPE_BASE + 99,
 }
+}
 
+masslynx_enum! {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum MassLynxHeaderItem {
     VERSION = HEADER_ITEM_BASE,
@@ -200,62 +307,11 @@ pub enum MassLynxHeaderItem {
     SPARE4 = 34 + HEADER_ITEM_BASE,
     SPARE5 = 35 + HEADER_ITEM_BASE,
 }
-
-impl MassLynxHeaderItem {
-    pub fn iter() -> impl Iterator<Item = Self> {
-        (HEADER_ITEM_BASE..).map_while(|i| {
-            (i as i32).try_into().ok()
-        })
-    }
-}
-
-impl TryFrom<i32> for MassLynxHeaderItem {
-    type Error = String;
-
-    fn try_from(value: i32) -> Result<Self, Self::Error> {
-        Ok(match value as u32 {
-            x if x  == Self::VERSION as u32 => Self::VERSION,
-            x if x  == Self::ACQUIRED_NAME as u32 => Self::ACQUIRED_NAME,
-            x if x  == Self::ACQUIRED_DATE as u32 => Self::ACQUIRED_DATE,
-            x if x  == Self::ACQUIRED_TIME as u32 => Self::ACQUIRED_TIME,
-            x if x  == Self::JOB_CODE as u32 => Self::JOB_CODE,
-            x if x  == Self::TASK_CODE as u32 => Self::TASK_CODE,
-            x if x  == Self::USER_NAME as u32 => Self::USER_NAME,
-            x if x  == Self::INSTRUMENT as u32 => Self::INSTRUMENT,
-            x if x  == Self::CONDITIONS as u32 => Self::CONDITIONS,
-            x if x  == Self::LAB_NAME as u32 => Self::LAB_NAME,
-            x if x  == Self::SAMPLE_DESCRIPTION as u32 => Self::SAMPLE_DESCRIPTION,
-            x if x  == Self::SOLVENT_DELAY as u32 => Self::SOLVENT_DELAY,
-            x if x  == Self::SUBMITTER as u32 => Self::SUBMITTER,
-            x if x  == Self::SAMPLE_ID as u32 => Self::SAMPLE_ID,
-            x if x  == Self::BOTTLE_NUMBER as u32 => Self::BOTTLE_NUMBER,
-            x if x  == Self::ANALOG_CH1_OFFSET as u32 => Self::ANALOG_CH1_OFFSET,
-            x if x  == Self::ANALOG_CH2_OFFSET as u32 => Self::ANALOG_CH2_OFFSET,
-            x if x  == Self::ANALOG_CH3_OFFSET as u32 => Self::ANALOG_CH3_OFFSET,
-            x if x  == Self::ANALOG_CH4_OFFSET as u32 => Self::ANALOG_CH4_OFFSET,
-            x if x  == Self::CAL_MS1_STATIC as u32 => Self::CAL_MS1_STATIC,
-            x if x  == Self::CAL_MS2_STATIC as u32 => Self::CAL_MS2_STATIC,
-            x if x  == Self::CAL_MS1_STATIC_PARAMS as u32 => Self::CAL_MS1_STATIC_PARAMS,
-            x if x  == Self::CAL_MS1_DYNAMIC_PARAMS as u32 => Self::CAL_MS1_DYNAMIC_PARAMS,
-            x if x  == Self::CAL_MS2_STATIC_PARAMS as u32 => Self::CAL_MS2_STATIC_PARAMS,
-            x if x  == Self::CAL_MS2_DYNAMIC_PARAMS as u32 => Self::CAL_MS2_DYNAMIC_PARAMS,
-            x if x  == Self::CAL_MS1_FAST_PARAMS as u32 => Self::CAL_MS1_FAST_PARAMS,
-            x if x  == Self::CAL_MS2_FAST_PARAMS as u32 => Self::CAL_MS2_FAST_PARAMS,
-            x if x  == Self::CAL_TIME as u32 => Self::CAL_TIME,
-            x if x  == Self::CAL_DATE as u32 => Self::CAL_DATE,
-            x if x  == Self::CAL_TEMPERATURE as u32 => Self::CAL_TEMPERATURE,
-            x if x  == Self::INLET_METHOD as u32 => Self::INLET_METHOD,
-            x if x  == Self::SPARE1 as u32 => Self::SPARE1,
-            x if x  == Self::SPARE2 as u32 => Self::SPARE2,
-            x if x  == Self::SPARE3 as u32 => Self::SPARE3,
-            x if x  == Self::SPARE4 as u32 => Self::SPARE4,
-            x if x  == Self::SPARE5 as u32 => Self::SPARE5,
-            _ => return Err(format!("Cannot convert {value} into MassLynxHeaderItem"))
-        })
-    }
 }
 
+masslynx_enum! {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum MassLynxScanItem {
     LINEAR_DETECTOR_VOLTAGE = SCAN_ITEM_BASE,
@@ -344,104 +400,12 @@ pub enum MassLynxScanItem {
     PEAKS_IN_SCAN = SCAN_ITEM_BASE + 254,
     UNINITIALISED = SCAN_ITEM_BASE + 298,
 }
-
-impl TryFrom<i32> for MassLynxScanItem {
-    type Error = String;
-
-    fn try_from(value: i32) -> Result<Self, Self::Error> {
-        Ok(match value as u32 {
-            x if x == Self::LINEAR_DETECTOR_VOLTAGE as u32 => Self::LINEAR_DETECTOR_VOLTAGE,
-            x if x == Self::LINEAR_SENSITIVITY as u32 => Self::LINEAR_SENSITIVITY,
-            x if x == Self::REFLECTRON_LENS_VOLTAGE as u32 => Self::REFLECTRON_LENS_VOLTAGE,
-            x if x == Self::REFLECTRON_DETECTOR_VOLTAGE as u32 => Self::REFLECTRON_DETECTOR_VOLTAGE,
-            x if x == Self::REFLECTRON_SENSITIVITY as u32 => Self::REFLECTRON_SENSITIVITY,
-            x if x == Self::LASER_REPETITION_RATE as u32 => Self::LASER_REPETITION_RATE,
-            x if x == Self::COURSE_LASER_CONTROL as u32 => Self::COURSE_LASER_CONTROL,
-            x if x == Self::FINE_LASER_CONTROL as u32 => Self::FINE_LASER_CONTROL,
-            x if x == Self::LASERAIM_XPOS as u32 => Self::LASERAIM_XPOS,
-            x if x == Self::LASERAIM_YPOS as u32 => Self::LASERAIM_YPOS,
-            x if x == Self::NUM_SHOTS_SUMMED as u32 => Self::NUM_SHOTS_SUMMED,
-            x if x == Self::NUM_SHOTS_PERFORMED as u32 => Self::NUM_SHOTS_PERFORMED,
-            x if x == Self::SEGMENT_NUMBER as u32 => Self::SEGMENT_NUMBER,
-            x if x == Self::LCMP_TFM_WELL as u32 => Self::LCMP_TFM_WELL,
-            x if x == Self::SEGMENT_TYPE as u32 => Self::SEGMENT_TYPE,
-            x if x == Self::SOURCE_REGION1 as u32 => Self::SOURCE_REGION1,
-            x if x == Self::SOURCE_REGION2 as u32 => Self::SOURCE_REGION2,
-            x if x == Self::REFLECTRON_FIELD_LENGTH as u32 => Self::REFLECTRON_FIELD_LENGTH,
-            x if x == Self::REFLECTRON_LENGTH as u32 => Self::REFLECTRON_LENGTH,
-            x if x == Self::REFLECTRON_VOLT as u32 => Self::REFLECTRON_VOLT,
-            x if x == Self::SAMPLE_PLATE_VOLT as u32 => Self::SAMPLE_PLATE_VOLT,
-            x if x == Self::REFLECTRON_FIELD_LENGTH_ALT as u32 => Self::REFLECTRON_FIELD_LENGTH_ALT,
-            x if x == Self::REFLECTRON_LENGTH_ALT as u32 => Self::REFLECTRON_LENGTH_ALT,
-            x if x == Self::PSD_STEP_MAJOR as u32 => Self::PSD_STEP_MAJOR,
-            x if x == Self::PSD_STEP_MINOR as u32 => Self::PSD_STEP_MINOR,
-            x if x == Self::PSD_FACTOR_1 as u32 => Self::PSD_FACTOR_1,
-            x if x == Self::NEEDLE as u32 => Self::NEEDLE,
-            x if x == Self::COUNTER_ELECTRODE_VOLTAGE as u32 => Self::COUNTER_ELECTRODE_VOLTAGE,
-            x if x == Self::SAMPLING_CONE_VOLTAGE as u32 => Self::SAMPLING_CONE_VOLTAGE,
-            x if x == Self::SKIMMER_LENS as u32 => Self::SKIMMER_LENS,
-            x if x == Self::SKIMMER as u32 => Self::SKIMMER,
-            x if x == Self::PROBE_TEMPERATURE as u32 => Self::PROBE_TEMPERATURE,
-            x if x == Self::SOURCE_TEMPERATURE as u32 => Self::SOURCE_TEMPERATURE,
-            x if x == Self::RF_VOLTAGE as u32 => Self::RF_VOLTAGE,
-            x if x == Self::SOURCE_APERTURE as u32 => Self::SOURCE_APERTURE,
-            x if x == Self::SOURCE_CODE as u32 => Self::SOURCE_CODE,
-            x if x == Self::LM_RESOLUTION as u32 => Self::LM_RESOLUTION,
-            x if x == Self::HM_RESOLUTION as u32 => Self::HM_RESOLUTION,
-            x if x == Self::COLLISION_ENERGY as u32 => Self::COLLISION_ENERGY,
-            x if x == Self::ION_ENERGY as u32 => Self::ION_ENERGY,
-            x if x == Self::MULTIPLIER1 as u32 => Self::MULTIPLIER1,
-            x if x == Self::MULTIPLIER2 as u32 => Self::MULTIPLIER2,
-            x if x == Self::TRANSPORTDC as u32 => Self::TRANSPORTDC,
-            x if x == Self::TOF_APERTURE as u32 => Self::TOF_APERTURE,
-            x if x == Self::ACC_VOLTAGE as u32 => Self::ACC_VOLTAGE,
-            x if x == Self::STEERING as u32 => Self::STEERING,
-            x if x == Self::FOCUS as u32 => Self::FOCUS,
-            x if x == Self::ENTRANCE as u32 => Self::ENTRANCE,
-            x if x == Self::GUARD as u32 => Self::GUARD,
-            x if x == Self::TOF as u32 => Self::TOF,
-            x if x == Self::REFLECTRON as u32 => Self::REFLECTRON,
-            x if x == Self::COLLISION_RF as u32 => Self::COLLISION_RF,
-            x if x == Self::TRANSPORT_RF as u32 => Self::TRANSPORT_RF,
-            x if x == Self::SET_MASS as u32 => Self::SET_MASS,
-            x if x == Self::COLLISION_ENERGY2 as u32 => Self::COLLISION_ENERGY2,
-            x if x == Self::SET_MASS_CALL_SUPPORTED as u32 => Self::SET_MASS_CALL_SUPPORTED,
-            x if x == Self::SET_MASS_CALIBRATED as u32 => Self::SET_MASS_CALIBRATED,
-            x if x == Self::SONAR_ENABLED as u32 => Self::SONAR_ENABLED,
-            x if x == Self::QUAD_START_MASS as u32 => Self::QUAD_START_MASS,
-            x if x == Self::QUAD_STOP_MASS as u32 => Self::QUAD_STOP_MASS,
-            x if x == Self::QUAD_PEAK_WIDTH as u32 => Self::QUAD_PEAK_WIDTH,
-            x if x == Self::REFERENCE_SCAN as u32 => Self::REFERENCE_SCAN,
-            x if x == Self::USE_LOCKMASS_CORRECTION as u32 => Self::USE_LOCKMASS_CORRECTION,
-            x if x == Self::LOCKMASS_CORRECTION as u32 => Self::LOCKMASS_CORRECTION,
-            x if x == Self::USETEMP_CORRECTION as u32 => Self::USETEMP_CORRECTION,
-            x if x == Self::TEMP_CORRECTION as u32 => Self::TEMP_CORRECTION,
-            x if x == Self::TEMP_COEFFICIENT as u32 => Self::TEMP_COEFFICIENT,
-            x if x == Self::FAIMS_COMPENSATION_VOLTAGE as u32 => Self::FAIMS_COMPENSATION_VOLTAGE,
-            x if x == Self::TIC_TRACE_A as u32 => Self::TIC_TRACE_A,
-            x if x == Self::TIC_TRACE_B as u32 => Self::TIC_TRACE_B,
-            x if x == Self::RAW_EE_CV as u32 => Self::RAW_EE_CV,
-            x if x == Self::RAW_EE_CE as u32 => Self::RAW_EE_CE,
-            x if x == Self::ACCURATE_MASS as u32 => Self::ACCURATE_MASS,
-            x if x == Self::ACCURATE_MASS_FLAGS as u32 => Self::ACCURATE_MASS_FLAGS,
-            x if x == Self::SCAN_ERROR_FLAG as u32 => Self::SCAN_ERROR_FLAG,
-            x if x == Self::DRE_TRANSMISSION as u32 => Self::DRE_TRANSMISSION,
-            x if x == Self::SCAN_PUSH_COUNT as u32 => Self::SCAN_PUSH_COUNT,
-            x if x == Self::RAW_STAT_SWAVE_NORMALISATION_FACTOR as u32 => Self::RAW_STAT_SWAVE_NORMALISATION_FACTOR,
-            x if x == Self::MIN_DRIFT_TIME_CHANNEL as u32 => Self::MIN_DRIFT_TIME_CHANNEL,
-            x if x == Self::MAX_DRIFT_TIME_CHANNEL as u32 => Self::MAX_DRIFT_TIME_CHANNEL,
-            x if x == Self::TOTAL_ION_CURRENT as u32 => Self::TOTAL_ION_CURRENT,
-            x if x == Self::BASE_PEAK_MASS as u32 => Self::BASE_PEAK_MASS,
-            x if x == Self::BASE_PEAK_INTENSITY as u32 => Self::BASE_PEAK_INTENSITY,
-            x if x == Self::PEAKS_IN_SCAN as u32 => Self::PEAKS_IN_SCAN,
-            x if x == Self::UNINITIALISED as u32 => Self::UNINITIALISED,
-            _ => return Err(format!("Cannot map {value} to MassLynxScanItem"))
-        })
-    }
 }
 
 const FILE_NAME: u32 = 700;
+masslynx_enum! {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum MassLynxSampleListItem {
     FILE_NAME = FILE_NAME,
@@ -563,140 +527,12 @@ pub enum MassLynxSampleListItem {
     METH_DB = FILE_NAME + 116,
     CURVE_DB = FILE_NAME + 117,
 }
-
-impl TryFrom<i32> for MassLynxSampleListItem {
-    type Error = String;
-
-    fn try_from(value: i32) -> Result<Self, Self::Error> {
-        let this = Self::ACQU_PROCESS_FILE;
-        Ok(match value as u32 {
-            FILE_NAME => Self::FILE_NAME,
-            x if x == FILE_NAME + 1 => Self::FILE_TEXT,
-            x if x == FILE_NAME + 2 => Self::MS_FILE,
-            x if x == FILE_NAME + 3 => Self::MS_TUNE_FILE,
-            x if x == FILE_NAME + 4 => Self::INLET_FILE,
-            x if x == FILE_NAME + 5 => Self::INLET_PRERUN,
-            x if x == FILE_NAME + 6 => Self::INLET_POSTRUN,
-            x if x == FILE_NAME + 7 => Self::INLET_SWITCH,
-            x if x == FILE_NAME + 8 => Self::AUTO_FILE,
-            x if x == FILE_NAME + 9 => Self::PROCESS,
-            x if x == FILE_NAME + 10 => Self::PROCESS_PARAMS,
-            x if x == FILE_NAME + 11 => Self::PROCESS_OPTIONS,
-            x if x == FILE_NAME + 12 => Self::ACQU_PROCESS_FILE,
-            x if x == FILE_NAME + 13 => Self::ACQU_PROCESS_PARAMS,
-            x if x == FILE_NAME + 14 => Self::ACQU_PROCESS_OPTIONS,
-            x if x == FILE_NAME + 15 => Self::PROCESS_ACTION,
-            x if x == FILE_NAME + 16 => Self::SAMPLE_LOCATION,
-            x if x == FILE_NAME + 17 => Self::SAMPLE_GROUP,
-            x if x == FILE_NAME + 18 => Self::JOB,
-            x if x == FILE_NAME + 19 => Self::TASK,
-            x if x == FILE_NAME + 20 => Self::USER,
-            x if x == FILE_NAME + 21 => Self::SUBMITTER,
-            x if x == FILE_NAME + 22 => Self::CONDITIONS,
-            x if x == FILE_NAME + 23 => Self::TYPE,
-            x if x == FILE_NAME + 24 => Self::CONTROL,
-            x if x == FILE_NAME + 25 => Self::ID,
-            x if x == FILE_NAME + 26 => Self::CONC_A,
-            x if x == FILE_NAME + 27 => Self::CONC_B,
-            x if x == FILE_NAME + 28 => Self::CONC_C,
-            x if x == FILE_NAME + 29 => Self::CONC_D,
-            x if x == FILE_NAME + 30 => Self::CONC_E,
-            x if x == FILE_NAME + 31 => Self::CONC_F,
-            x if x == FILE_NAME + 32 => Self::CONC_G,
-            x if x == FILE_NAME + 33 => Self::CONC_H,
-            x if x == FILE_NAME + 34 => Self::CONC_I,
-            x if x == FILE_NAME + 35 => Self::CONC_J,
-            x if x == FILE_NAME + 36 => Self::CONC_K,
-            x if x == FILE_NAME + 37 => Self::CONC_L,
-            x if x == FILE_NAME + 38 => Self::CONC_M,
-            x if x == FILE_NAME + 39 => Self::CONC_N,
-            x if x == FILE_NAME + 40 => Self::CONC_O,
-            x if x == FILE_NAME + 41 => Self::CONC_P,
-            x if x == FILE_NAME + 42 => Self::CONC_Q,
-            x if x == FILE_NAME + 43 => Self::CONC_R,
-            x if x == FILE_NAME + 44 => Self::CONC_S,
-            x if x == FILE_NAME + 45 => Self::CONC_T,
-            x if x == FILE_NAME + 46 => Self::WAVELENGTH_A,
-            x if x == FILE_NAME + 47 => Self::WAVELENGTH_B,
-            x if x == FILE_NAME + 48 => Self::WAVELENGTH_C,
-            x if x == FILE_NAME + 49 => Self::WAVELENGTH_D,
-            x if x == FILE_NAME + 50 => Self::WAVELENGTH_E,
-            x if x == FILE_NAME + 51 => Self::WAVELENGTH_F,
-            x if x == FILE_NAME + 52 => Self::WAVELENGTH_G,
-            x if x == FILE_NAME + 53 => Self::WAVELENGTH_H,
-            x if x == FILE_NAME + 54 => Self::WAVELENGTH_I,
-            x if x == FILE_NAME + 55 => Self::WAVELENGTH_J,
-            x if x == FILE_NAME + 56 => Self::MASS_A,
-            x if x == FILE_NAME + 57 => Self::MASS_B,
-            x if x == FILE_NAME + 58 => Self::MASS_C,
-            x if x == FILE_NAME + 59 => Self::MASS_D,
-            x if x == FILE_NAME + 60 => Self::MASS_E,
-            x if x == FILE_NAME + 61 => Self::MASS_F,
-            x if x == FILE_NAME + 62 => Self::MASS_G,
-            x if x == FILE_NAME + 63 => Self::MASS_H,
-            x if x == FILE_NAME + 64 => Self::MASS_I,
-            x if x == FILE_NAME + 65 => Self::MASS_J,
-            x if x == FILE_NAME + 66 => Self::MASS_K,
-            x if x == FILE_NAME + 67 => Self::MASS_L,
-            x if x == FILE_NAME + 68 => Self::MASS_M,
-            x if x == FILE_NAME + 69 => Self::MASS_N,
-            x if x == FILE_NAME + 70 => Self::MASS_O,
-            x if x == FILE_NAME + 71 => Self::MASS_P,
-            x if x == FILE_NAME + 72 => Self::MASS_Q,
-            x if x == FILE_NAME + 73 => Self::MASS_R,
-            x if x == FILE_NAME + 74 => Self::MASS_S,
-            x if x == FILE_NAME + 75 => Self::MASS_T,
-            x if x == FILE_NAME + 76 => Self::MASS_U,
-            x if x == FILE_NAME + 77 => Self::MASS_V,
-            x if x == FILE_NAME + 78 => Self::MASS_W,
-            x if x == FILE_NAME + 79 => Self::MASS_X,
-            x if x == FILE_NAME + 80 => Self::MASS_Y,
-            x if x == FILE_NAME + 81 => Self::MASS_Z,
-            x if x == FILE_NAME + 82 => Self::MASS_AA,
-            x if x == FILE_NAME + 83 => Self::MASS_BB,
-            x if x == FILE_NAME + 84 => Self::MASS_CC,
-            x if x == FILE_NAME + 85 => Self::MASS_DD,
-            x if x == FILE_NAME + 86 => Self::FRACTION_FILE,
-            x if x == FILE_NAME + 87 => Self::FRACTION_1,
-            x if x == FILE_NAME + 88 => Self::FRACTION_2,
-            x if x == FILE_NAME + 89 => Self::FRACTION_3,
-            x if x == FILE_NAME + 90 => Self::FRACTION_4,
-            x if x == FILE_NAME + 91 => Self::FRACTION_5,
-            x if x == FILE_NAME + 92 => Self::FRACTION_6,
-            x if x == FILE_NAME + 93 => Self::FRACTION_7,
-            x if x == FILE_NAME + 94 => Self::FRACTION_8,
-            x if x == FILE_NAME + 95 => Self::FRACTION_9,
-            x if x == FILE_NAME + 96 => Self::FRACTION_10,
-            x if x == FILE_NAME + 97 => Self::FRACTION_BOOLEAN_LOGIC,
-            x if x == FILE_NAME + 98 => Self::FRACTION_START,
-            x if x == FILE_NAME + 99 => Self::INJ_VOL,
-            x if x == FILE_NAME + 100 => Self::STOCK_DIL,
-            x if x == FILE_NAME + 101 => Self::USER_DIVISOR_1,
-            x if x == FILE_NAME + 102 => Self::USER_FACTOR_1,
-            x if x == FILE_NAME + 103 => Self::USER_FACTOR_2,
-            x if x == FILE_NAME + 104 => Self::USER_FACTOR_3,
-            x if x == FILE_NAME + 105 => Self::SPARE_1,
-            x if x == FILE_NAME + 106 => Self::SPARE_2,
-            x if x == FILE_NAME + 107 => Self::SPARE_3,
-            x if x == FILE_NAME + 108 => Self::SPARE_4,
-            x if x == FILE_NAME + 109 => Self::SPARE_5,
-            x if x == FILE_NAME + 110 => Self::HPLC_FILE,
-            x if x == FILE_NAME + 111 => Self::QUAN_REF,
-            x if x == FILE_NAME + 112 => Self::AUTO_ADDITION,
-            x if x == FILE_NAME + 113 => Self::MOLFILE,
-            x if x == FILE_NAME + 114 => Self::SUBJECTTEXT,
-            x if x == FILE_NAME + 115 => Self::SUBJECTTIME,
-            x if x == FILE_NAME + 116 => Self::METH_DB,
-            x if x == FILE_NAME + 117 => Self::CURVE_DB,
-            _ => return Err(format!("No mapping for {value} to MassLynxSampleListItem"))
-        })
-    }
 }
 
-impl_as_key!(MassLynxHeaderItem, MassLynxScanItem, MassLynxSampleListItem, );
-
 
+masslynx_enum! {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum MassLynxBatchItem {
 	SAMPLELIST_NAME = BATCH_ITEM_BASE,
@@ -705,9 +541,12 @@ pub enum MassLynxBatchItem {
 	CURRENT_SAMPLE = BATCH_ITEM_BASE + 3,
 	BATCH_USER_NAME = BATCH_ITEM_BASE + 4
 }
+}
 
 
+masslynx_enum! {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum MassLynxAcquisitionType {
 	DDA = ACQUISITION_TYPE_BASE,
@@ -718,60 +557,87 @@ pub enum MassLynxAcquisitionType {
 	UNKNOWN = ACQUISITION_TYPE_BASE + 48,
 	UNINITIALISED = ACQUISITION_TYPE_BASE + 49
 }
-
-impl TryFrom<i32> for MassLynxAcquisitionType {
-    type Error = String;
-
-    fn try_from(value: i32) -> Result<Self, Self::Error> {
-        Ok(match value as u32 {
-            x if x == Self::DDA as u32 => Self::DDA,
-            x if x == Self::MSE as u32 => Self::MSE,
-            x if x == Self::HDDDA as u32 => Self::HDDDA,
-            x if x == Self::HDMSE as u32 => Self::HDMSE,
-            x if x == Self::SONAR as u32 => Self::SONAR,
-            x if x == Self::UNKNOWN as u32 => Self::UNKNOWN,
-            x if x == Self::UNINITIALISED as u32 => Self::UNINITIALISED,
-            _ => return Err(format!("Cannot convert {value} into MassLynxAcquisitionType")),
-        })
-    }
 }
 
-impl_as_key!(MassLynxAcquisitionType);
-
 
+masslynx_enum! {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum MassLynxScanType {
 	MS1 = SCAN_TYPE_BASE,
 	MS2 = SCAN_TYPE_BASE + 1,
 	UNINITIALISED = SCAN_TYPE_BASE + 9
 }
+}
 
+masslynx_enum! {
+/// Whether a run is still being acquired
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u32)]
+pub enum MassLynxStatusType {
+	ACQUIRING = STATUS_TYPE_BASE,
+	COMPLETED = STATUS_TYPE_BASE + 1,
+	ABORTED = STATUS_TYPE_BASE + 2,
+	UNINITIALISED = STATUS_TYPE_BASE + 9
+}
+}
+
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum LockMassParameter {
     MASS = LOCKMASS_ITEM_BASE,
     TOLERANCE = LOCKMASS_ITEM_BASE + 1,
     FORCE = LOCKMASS_ITEM_BASE + 2,
 }
+}
 
-impl TryFrom<i32> for LockMassParameter {
-    type Error = String;
 
-    fn try_from(value: i32) -> Result<Self, Self::Error> {
-        Ok(match value as u32 {
-            LOCKMASS_ITEM_BASE => Self::MASS,
-            x if x == Self::TOLERANCE as u32 => Self::TOLERANCE,
-            x if x == Self::FORCE as u32 => Self::FORCE,
-            _ => return Err(format!("Could not convert {value} to LockMassParameter"))
-        })
-    }
+/// Reference compounds commonly infused as a lock mass. This is not an SDK key, so it is not
+/// an [`AsMassLynxItemKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LockMassCompound {
+    LEUCINE_ENKEPHALIN,
+    GLU_FIBRINOPEPTIDE_B,
+    RESERPINE,
+    CAFFEINE,
 }
 
-impl_as_key!(LockMassParameter);
+impl LockMassCompound {
+    /// Every variant, in declaration order
+    pub const ALL: &'static [Self] = &[
+        Self::LEUCINE_ENKEPHALIN,
+        Self::GLU_FIBRINOPEPTIDE_B,
+        Self::RESERPINE,
+        Self::CAFFEINE,
+    ];
 
+    /// Iterate over every variant, in declaration order
+    pub fn iter() -> impl Iterator<Item = Self> {
+        Self::ALL.iter().copied()
+    }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    /// The m/z of the ion used as the lock mass reference in `polarity`, or `None` if the
+    /// compound is not used in that polarity
+    pub fn reference_mz(&self, polarity: Polarity) -> Option<f32> {
+        match (self, polarity) {
+            (Self::LEUCINE_ENKEPHALIN, Polarity::Positive) => Some(556.2771),
+            (Self::LEUCINE_ENKEPHALIN, Polarity::Negative) => Some(554.2615),
+            (Self::GLU_FIBRINOPEPTIDE_B, Polarity::Positive) => Some(785.8426),
+            (Self::RESERPINE, Polarity::Positive) => Some(609.2807),
+            (Self::CAFFEINE, Polarity::Positive) => Some(195.0877),
+            _ => None,
+        }
+    }
+}
+
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum FunctionDefinition {
     CONTINUUM = FUNCTION_DEFINITION_BASE,
@@ -784,24 +650,33 @@ pub enum FunctionDefinition {
     LTEFF = FUNCTION_DEFINITION_BASE + 7,
     VEFF = FUNCTION_DEFINITION_BASE + 8,
 }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum AnalogParameter {
     DESCRIPTION = ANALOG_PARAMETER_BASE + 1,
     UNITS = ANALOG_PARAMETER_BASE + 2,
     TYPE = ANALOG_PARAMETER_BASE + 3,
 }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum AnalogTraceType {
     ANALOG = ANALOG_TYPE_BASE,
     ELSD = ANALOG_TYPE_BASE + 1,
     READBACK = ANALOG_TYPE_BASE + 2,
 }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum AutoLynxStatus {
     QUEUED = AUTOLYNX_STATUS_BASE,
@@ -810,16 +685,35 @@ pub enum AutoLynxStatus {
     NOTFOUND = AUTOLYNX_STATUS_BASE + 3,
     UNINITIALISED = AUTOLYNX_STATUS_BASE + 9,
 }
+}
 
+masslynx_enum! {
+/// The keys of the AutoLynx queue settings
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u32)]
+pub enum AutoLynxSettings {
+    QUEUE_PATH = AUTOLYNX_SETTINGS_BASE,
+    IMPORT_PATH = AUTOLYNX_SETTINGS_BASE + 1,
+    QUEUE_TYPE = AUTOLYNX_SETTINGS_BASE + 2,
+    JSON = AUTOLYNX_SETTINGS_BASE + 3,
+}
+}
+
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum CentroidParameter
 {
 	RESOLUTION = CENTROID_ITEM_BASE
 }
+}
 
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum MassLynxDDAIndexDetail {
 	RT = DDA_TYPE_BASE,
@@ -830,49 +724,78 @@ pub enum MassLynxDDAIndexDetail {
 	SET_MASS = DDA_TYPE_BASE + 5,
 	PRECURSOR_MASS = DDA_TYPE_BASE + 6
 }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum DDAIsolationWindowParameter {
 	LOWEROFFSET = DDA_ISOLATION_WINDOW_PARAMETER_BASE,
 	UPPEROFFSET = DDA_ISOLATION_WINDOW_PARAMETER_BASE + 1
 }
+}
 
+masslynx_enum! {
+/// The keys of the parameters used to group the scans of a DDA run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u32)]
+pub enum DDAParameter {
+	RT_TOLERANCE = DDA_PARAMETER_BASE,
+	RT_WINDOW = DDA_PARAMETER_BASE + 1
+}
+}
 
+
+masslynx_enum! {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum SmoothParameter {
 	NUMBER = SMOOTH_ITEM_BASE,
 	WIDTH = SMOOTH_ITEM_BASE + 1,
 	SMOOTHTYPE = SMOOTH_ITEM_BASE + 2
 }
+}
 
+masslynx_enum! {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum SmoothType {
 	MEAN = SMOOTH_TYPE_BASE,
 	MEDIAN = SMOOTH_TYPE_BASE + 1,
 	SAVITZKY_GOLAY = SMOOTH_TYPE_BASE + 2
 }
+}
 
 
+masslynx_enum! {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum ThresholdParameter {
 	VALUE = THESHOLD_ITEM_BASE,
 	TYPE = THESHOLD_ITEM_BASE + 1
 }
+}
 
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+masslynx_enum! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum ThresholdType {
 	ABSOLUTE_THESHOLD = THESHOLD_TYPE_BASE,
 	RELATIVE_THESHOLD = THESHOLD_TYPE_BASE + 1
 }
+}
 
 
+masslynx_enum! {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum AcquisitionParameter {
 	TYPE = ACQUISITION_PARAMETER_BASE,
@@ -887,26 +810,471 @@ pub enum AcquisitionParameter {
 	VEFF = ACQUISITION_PARAMETER_BASE + 9,
 	RESOLUTION = ACQUISITION_PARAMETER_BASE + 10
 }
+}
 
-impl TryFrom<i32> for AcquisitionParameter {
-    type Error = String;
 
-    fn try_from(value: i32) -> Result<Self, Self::Error> {
-        Ok(match value as u32 {
-            x if x == Self::TYPE as u32 => Self::TYPE,
-            x if x == Self::LOCKMASS as u32 => Self::LOCKMASS,
-            x if x == Self::MS1 as u32 => Self::MS1,
-            x if x == Self::MS2 as u32 => Self::MS2,
-            x if x == Self::PRECURSOR_MASS_START as u32 => Self::PRECURSOR_MASS_START,
-            x if x == Self::PRECURSOR_MASS_END as u32 => Self::PRECURSOR_MASS_END,
-            x if x == Self::FUNCTIONS as u32 => Self::FUNCTIONS,
-            x if x == Self::SAMPLINGFREQUENCY as u32 => Self::SAMPLINGFREQUENCY,
-            x if x == Self::LTEFF as u32 => Self::LTEFF,
-            x if x == Self::VEFF as u32 => Self::VEFF,
-            x if x == Self::RESOLUTION as u32 => Self::RESOLUTION,
-            _ => return Err(format!("Cannot convert {value} into AcquisitionParameter")),
+/// Words in variant names that are written in upper case when displayed
+const ACRONYMS: &[&str] = &[
+    "CE", "CH", "CV", "DC", "DRE", "EE", "FAIMS", "HM", "ID", "LCMP", "LM", "MS", "PSD", "RF",
+    "TFM", "TIC", "TOF",
+];
+
+/// Turn a `SCREAMING_SNAKE_CASE` variant name into a title-cased label, e.g.
+/// `SAMPLING_CONE_VOLTAGE` becomes "Sampling Cone Voltage" and `CAL_MS1_STATIC` becomes
+/// "Cal MS1 Static"
+fn friendly_name(name: &str) -> String {
+    name.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let stem = word.trim_end_matches(|c: char| c.is_ascii_digit());
+            if ACRONYMS.contains(&stem) {
+                word.to_string()
+            } else {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .into_iter()
+                    .chain(chars.flat_map(|c| c.to_lowercase()))
+                    .collect()
+            }
         })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+macro_rules! impl_friendly_display {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl Display for $t {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str(&friendly_name(&format!("{self:?}")))
+                }
+            }
+        )+
+    };
+}
+
+impl_friendly_display!(MassLynxScanItem, MassLynxHeaderItem, AcquisitionParameter);
+
+impl MassLynxFunctionType {
+    /// The MS level of the spectra this function type produces, or 0 for functions
+    /// that are not read as spectra
+    pub fn default_ms_level(&self) -> u8 {
+        match self {
+            Self::MS | Self::TOF | Self::TOFM | Self::PAR | Self::MTOF | Self::TOFP => 1,
+            Self::MS2 | Self::TOFD | Self::DAU => 2,
+            _ => 0,
+        }
+    }
+
+    /// Whether the function is a tandem MS experiment, selecting precursors and
+    /// fragmenting them. Parent scans are included even though their spectra are MS1.
+    pub fn is_msms(&self) -> bool {
+        matches!(
+            self,
+            Self::PAR
+                | Self::DAU
+                | Self::NL
+                | Self::NG
+                | Self::MRM
+                | Self::PSD
+                | Self::TOFD
+                | Self::TOFP
+                | Self::QUADD
+                | Self::ASMRM
+                | Self::ASMRMQ
+        )
+    }
+
+    /// Whether the function only records chromatograms, like diode array, SIR and MRM
+    /// functions
+    pub fn is_chromatographic_only(&self) -> bool {
+        matches!(
+            self,
+            Self::DAD
+                | Self::SIR
+                | Self::MRM
+                | Self::ASVSIR
+                | Self::ASMSIR
+                | Self::ASMRM
+                | Self::ASMRMQ
+        )
+    }
+
+    /// Whether the function's scans are read as spectra, see
+    /// [`MassLynxFunctionType::default_ms_level`]
+    pub fn produces_spectra(&self) -> bool {
+        self.default_ms_level() > 0
+    }
+}
+
+/// The polarity of the ions a function acquired
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Polarity {
+    Positive,
+    Negative,
+    #[default]
+    Unknown,
+}
+
+/// The ionization source of an ion mode
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ionization {
+    /// Electron ionization
+    EI,
+    /// Chemical ionization
+    CI,
+    /// Fast atom bombardment
+    FAB,
+    /// Thermospray
+    TS,
+    /// Electrospray ionization
+    ESI,
+    /// Atmospheric pressure chemical ionization
+    APCI,
+    /// Laser desorption, including MALDI
+    LD,
+    #[default]
+    Unknown,
+}
+
+impl MassLynxIonMode {
+    pub fn polarity(&self) -> Polarity {
+        match self {
+            Self::EI_POS
+            | Self::CI_POS
+            | Self::FB_POS
+            | Self::TS_POS
+            | Self::ES_POS
+            | Self::AI_POS
+            | Self::LD_POS => Polarity::Positive,
+            Self::EI_NEG
+            | Self::CI_NEG
+            | Self::FB_NEG
+            | Self::TS_NEG
+            | Self::ES_NEG
+            | Self::AI_NEG
+            | Self::LD_NEG => Polarity::Negative,
+            Self::UNINITIALISED => Polarity::Unknown,
+        }
+    }
+
+    pub fn ionization(&self) -> Ionization {
+        match self {
+            Self::EI_POS | Self::EI_NEG => Ionization::EI,
+            Self::CI_POS | Self::CI_NEG => Ionization::CI,
+            Self::FB_POS | Self::FB_NEG => Ionization::FAB,
+            Self::TS_POS | Self::TS_NEG => Ionization::TS,
+            Self::ES_POS | Self::ES_NEG => Ionization::ESI,
+            Self::AI_POS | Self::AI_NEG => Ionization::APCI,
+            Self::LD_POS | Self::LD_NEG => Ionization::LD,
+            Self::UNINITIALISED => Ionization::Unknown,
+        }
+    }
+}
+
+/// The polarity-signed source labels MassLynx uses, e.g. "ES+". The SDK's own wording is
+/// available from [`MassLynxReader::ion_mode_string`](crate::reader::MassLynxReader::ion_mode_string).
+impl Display for MassLynxIonMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::EI_POS => "EI+",
+            Self::EI_NEG => "EI-",
+            Self::CI_POS => "CI+",
+            Self::CI_NEG => "CI-",
+            Self::FB_POS => "FAB+",
+            Self::FB_NEG => "FAB-",
+            Self::TS_POS => "TS+",
+            Self::TS_NEG => "TS-",
+            Self::ES_POS => "ES+",
+            Self::ES_NEG => "ES-",
+            Self::AI_POS => "APCI+",
+            Self::AI_NEG => "APCI-",
+            Self::LD_POS => "LD+",
+            Self::LD_NEG => "LD-",
+            Self::UNINITIALISED => "Uninitialised",
+        };
+        f.write_str(label)
+    }
+}
+
+/// The function type names MassLynx uses. The SDK's own wording is available from
+/// [`MassLynxReader::function_type_string`](crate::reader::MassLynxReader::function_type_string).
+impl Display for MassLynxFunctionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::MS => "MS",
+            Self::SIR => "SIR",
+            Self::DLY => "Delay",
+            Self::CAT => "Concatenated",
+            Self::OFF => "Off",
+            Self::PAR => "Parents",
+            Self::DAU => "Daughters",
+            Self::NL => "Neutral Loss",
+            Self::NG => "Neutral Gain",
+            Self::MRM => "MRM",
+            Self::Q1F => "Q1F",
+            Self::MS2 => "MS2",
+            Self::DAD => "Diode Array",
+            Self::TOF => "TOF",
+            Self::PSD => "TOF PSD",
+            Self::TOFS => "TOF Survey",
+            Self::TOFD => "TOF Daughter",
+            Self::MTOF => "MALDI TOF",
+            Self::TOFM => "TOF MS",
+            Self::TOFP => "TOF Parent",
+            Self::ASVS => "Voltage Scan",
+            Self::ASMS => "Magnetic Scan",
+            Self::ASVSIR => "Voltage SIR",
+            Self::ASMSIR => "Magnetic SIR",
+            Self::QUADD => "Auto Daughters",
+            Self::ASBE => "AutoSpec B/E Scan",
+            Self::ASB2E => "AutoSpec B2/E Scan",
+            Self::ASCNL => "AutoSpec CNL Scan",
+            Self::ASMIKES => "AutoSpec MIKES Scan",
+            Self::ASMRM => "AutoSpec MRM",
+            Self::ASNRMS => "AutoSpec NRMS Scan",
+            Self::ASMRMQ => "AutoSpec Q MRM Quad",
+            Self::UNINITIALISED => "Uninitialised",
+        };
+        f.write_str(label)
     }
 }
 
-impl_as_key!(AcquisitionParameter);
\ No newline at end of file
+/// Lower-case `name` and drop the separators, so that `SAVITZKY_GOLAY`, "savitzky-golay" and
+/// "Savitzky Golay" compare equal. A trailing `-` is a polarity sign, as in "ES-", and is kept
+/// so that it stays distinct from "ES+" and "ES".
+fn normalize_name(name: &str) -> String {
+    let name = name.trim();
+    let (body, sign) = match name.strip_suffix('-') {
+        Some(body) => (body, "-"),
+        None => (name, ""),
+    };
+    body.chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .flat_map(|c| c.to_lowercase())
+        .chain(sign.chars())
+        .collect()
+}
+
+/// Find the variant named by `value`, matching each variant's `labels` after normalization.
+/// If nothing matches exactly, a prefix that matches only one variant is accepted.
+fn parse_variant<T: Copy>(
+    value: &str,
+    variants: &[T],
+    labels: impl Fn(&T) -> Vec<String>,
+    type_name: &str,
+) -> Result<T, String> {
+    let key = normalize_name(value);
+    if key.is_empty() {
+        return Err(format!("Cannot parse an empty string into {type_name}"));
+    }
+    let labels: Vec<(T, Vec<String>)> = variants
+        .iter()
+        .map(|v| (*v, labels(v).iter().map(|l| normalize_name(l)).collect()))
+        .collect();
+    if let Some((v, _)) = labels.iter().find(|(_, ls)| ls.contains(&key)) {
+        return Ok(*v);
+    }
+    let mut candidates = labels
+        .iter()
+        .filter(|(_, ls)| ls.iter().any(|l| l.starts_with(&key)));
+    match (candidates.next(), candidates.next()) {
+        (Some((v, _)), None) => Ok(*v),
+        (Some(_), Some(_)) => Err(format!("{value:?} is ambiguous for {type_name}")),
+        _ => Err(format!("Cannot parse {value:?} into {type_name}")),
+    }
+}
+
+/// Implement [`FromStr`] by variant name, and also by [`Display`] label for the `display:` form
+macro_rules! impl_from_str {
+    (display: $($t:ty),+ $(,)?) => {
+        $(
+            impl FromStr for $t {
+                type Err = String;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    parse_variant(
+                        s,
+                        Self::ALL,
+                        |v| vec![format!("{v:?}"), v.to_string()],
+                        stringify!($t),
+                    )
+                }
+            }
+        )+
+    };
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl FromStr for $t {
+                type Err = String;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    parse_variant(s, Self::ALL, |v| vec![format!("{v:?}")], stringify!($t))
+                }
+            }
+        )+
+    };
+}
+
+impl_from_str!(display: MassLynxIonMode, MassLynxFunctionType);
+impl_from_str!(SmoothType, ThresholdType, MassLynxAcquisitionType, LockMassCompound);
+
+impl TryFrom<&str> for LockMassCompound {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// The physical unit of a scan item's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MassLynxUnit {
+    Volt,
+    ElectronVolt,
+    Celsius,
+    Millisecond,
+    Millimeter,
+    Hertz,
+    MZ,
+    Percent,
+    Count,
+    Dimensionless,
+}
+
+impl MassLynxUnit {
+    /// The unit's symbol, empty for [`MassLynxUnit::Dimensionless`]
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Volt => "V",
+            Self::ElectronVolt => "eV",
+            Self::Celsius => "°C",
+            Self::Millisecond => "ms",
+            Self::Millimeter => "mm",
+            Self::Hertz => "Hz",
+            Self::MZ => "m/z",
+            Self::Percent => "%",
+            Self::Count => "counts",
+            Self::Dimensionless => "",
+        }
+    }
+}
+
+impl Display for MassLynxUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl MassLynxScanItem {
+    /// The physical unit the item's value is reported in, where the item's name states it.
+    ///
+    /// The SDK does not document the units of its scan items, so this is `None` for every
+    /// item whose unit would be a guess, e.g. collision energies and temperatures.
+    pub fn unit(&self) -> Option<MassLynxUnit> {
+        match self {
+            Self::LINEAR_DETECTOR_VOLTAGE
+            | Self::REFLECTRON_LENS_VOLTAGE
+            | Self::REFLECTRON_DETECTOR_VOLTAGE
+            | Self::REFLECTRON_VOLT
+            | Self::SAMPLE_PLATE_VOLT
+            | Self::COUNTER_ELECTRODE_VOLTAGE
+            | Self::SAMPLING_CONE_VOLTAGE
+            | Self::RF_VOLTAGE
+            | Self::ACC_VOLTAGE
+            | Self::FAIMS_COMPENSATION_VOLTAGE => Some(MassLynxUnit::Volt),
+            Self::SET_MASS
+            | Self::QUAD_START_MASS
+            | Self::QUAD_STOP_MASS
+            | Self::QUAD_PEAK_WIDTH
+            | Self::ACCURATE_MASS
+            | Self::BASE_PEAK_MASS => Some(MassLynxUnit::MZ),
+            Self::NUM_SHOTS_SUMMED
+            | Self::NUM_SHOTS_PERFORMED
+            | Self::SCAN_PUSH_COUNT
+            | Self::PEAKS_IN_SCAN => Some(MassLynxUnit::Count),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ion_mode_polarity() {
+        assert_eq!("es+".parse::<MassLynxIonMode>(), Ok(MassLynxIonMode::ES_POS));
+        assert_eq!("es-".parse::<MassLynxIonMode>(), Ok(MassLynxIonMode::ES_NEG));
+        assert_eq!("ES-".parse::<MassLynxIonMode>(), Ok(MassLynxIonMode::ES_NEG));
+        assert_eq!("ES_POS".parse::<MassLynxIonMode>(), Ok(MassLynxIonMode::ES_POS));
+        assert_eq!("APCI-".parse::<MassLynxIonMode>(), Ok(MassLynxIonMode::AI_NEG));
+    }
+
+    #[test]
+    fn test_parse_ion_mode_without_polarity_is_ambiguous() {
+        for source in ["es", "ES", "apci", "ei"] {
+            let err = source.parse::<MassLynxIonMode>().unwrap_err();
+            assert!(err.contains("ambiguous"), "{source}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_autolynx_keys() {
+        assert_eq!(AutoLynxSettings::QUEUE_PATH.as_key(), 1350);
+        assert_eq!(AutoLynxSettings::try_from(1353), Ok(AutoLynxSettings::JSON));
+        assert!(AutoLynxSettings::try_from(1354).is_err());
+        assert_eq!(AutoLynxStatus::try_from(1309), Ok(AutoLynxStatus::UNINITIALISED));
+    }
+
+    #[test]
+    fn test_dda_keys() {
+        assert_eq!(DDAParameter::RT_TOLERANCE.as_key(), 1950);
+        assert_eq!(DDAParameter::try_from(1951), Ok(DDAParameter::RT_WINDOW));
+        assert!(DDAParameter::try_from(1900).is_err());
+        assert_eq!(DDAParameter::iter().count(), 2);
+    }
+
+    #[test]
+    fn test_status_type_keys() {
+        assert_eq!(MassLynxStatusType::ACQUIRING.as_key(), 1750);
+        assert_eq!(MassLynxStatusType::try_from(1752), Ok(MassLynxStatusType::ABORTED));
+        assert_eq!(
+            MassLynxStatusType::try_from(1759),
+            Ok(MassLynxStatusType::UNINITIALISED)
+        );
+        assert!(MassLynxStatusType::try_from(1753).is_err());
+    }
+
+    #[test]
+    fn test_lock_mass_compound() {
+        assert_eq!(
+            LockMassCompound::try_from("leucine-enkephalin"),
+            Ok(LockMassCompound::LEUCINE_ENKEPHALIN)
+        );
+        assert_eq!("caffeine".parse(), Ok(LockMassCompound::CAFFEINE));
+        assert!(LockMassCompound::try_from("water").is_err());
+        assert_eq!(LockMassCompound::iter().count(), 4);
+    }
+
+    #[test]
+    fn test_scan_item_unit() {
+        assert_eq!(MassLynxScanItem::SAMPLING_CONE_VOLTAGE.unit(), Some(MassLynxUnit::Volt));
+        assert_eq!(MassLynxScanItem::SET_MASS.unit(), Some(MassLynxUnit::MZ));
+        assert_eq!(MassLynxScanItem::ION_ENERGY.unit(), None);
+        assert_eq!(MassLynxScanItem::COLLISION_ENERGY.unit(), None);
+        assert_eq!(MassLynxScanItem::SOURCE_TEMPERATURE.unit(), None);
+    }
+
+    #[test]
+    fn test_parse_inner_separators() {
+        assert_eq!(
+            "savitzky-golay".parse::<SmoothType>(),
+            Ok(SmoothType::SAVITZKY_GOLAY)
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Writers for exporting spectra as peak lists in common text formats.
+//!
+//! [`write_mgf`] and [`write_msp`] write spectra read by [`MassLynxReader`](crate::reader::MassLynxReader)
+//! as Mascot Generic Format and NIST MSP respectively. The title of each entry and the
+//! precursor fields are controlled by [`PeakListOptions`].
+//!
+//! [`write_delimited_pairs`], [`Spectrum::to_csv`] and [`Trace::to_csv`] write two-column
+//! delimited text, controlled by [`DelimitedOptions`].
+
+use std::io::{self, Write};
+
+use crate::constants::MassLynxScanItem;
+use crate::reader::{parse_scan_item_number, Spectrum, Trace};
+
+/// Controls how spectra are described in a peak list
+#[derive(Debug, Clone)]
+pub struct PeakListOptions {
+    /// The template for each entry's title. These placeholders are replaced:
+    ///
+    /// - `{run}` - [`PeakListOptions::run_name`]
+    /// - `{index}` - The spectrum index
+    /// - `{function}` - The function number, starting from 1
+    /// - `{scan}` - The scan number within the function, starting from 1
+    /// - `{time}` - The retention time in minutes
+    /// - `{id}` - The native ID
+    pub title_template: String,
+    /// The name of the run, used for the `{run}` placeholder
+    pub run_name: String,
+    /// The scan item holding the precursor m/z. No precursor is written if `None` or if the
+    /// spectrum does not have the item
+    pub precursor_item: Option<MassLynxScanItem>,
+    /// The precursor charge to write, if known
+    pub precursor_charge: Option<i32>,
+    /// Additional fields to write for each entry, named by the first element, taken from
+    /// the spectrum's scan items
+    pub extra_fields: Vec<(String, MassLynxScanItem)>,
+}
+
+impl Default for PeakListOptions {
+    fn default() -> Self {
+        Self {
+            title_template: "{run} {id}".to_string(),
+            run_name: String::new(),
+            precursor_item: Some(MassLynxScanItem::SET_MASS),
+            precursor_charge: None,
+            extra_fields: vec![(
+                "COLLISION_ENERGY".to_string(),
+                MassLynxScanItem::COLLISION_ENERGY,
+            )],
+        }
+    }
+}
+
+impl PeakListOptions {
+    pub fn new(run_name: String) -> Self {
+        Self {
+            run_name,
+            ..Self::default()
+        }
+    }
+
+    /// Expand [`PeakListOptions::title_template`] for `spectrum`
+    pub fn title_for(&self, spectrum: &Spectrum) -> String {
+        let scan = spectrum
+            .identifier
+            .drift_index
+            .map(|i| i as usize)
+            .unwrap_or(spectrum.identifier.cycle);
+        self.title_template
+            .replace("{run}", &self.run_name)
+            .replace("{index}", &spectrum.index.to_string())
+            .replace(
+                "{function}",
+                &(spectrum.identifier.function + 1).to_string(),
+            )
+            .replace("{scan}", &(scan + 1).to_string())
+            .replace("{time}", &spectrum.time.to_string())
+            .replace("{id}", &spectrum.native_id())
+            .trim()
+            .to_string()
+    }
+
+    fn precursor_mz<'a>(&self, spectrum: &'a Spectrum) -> Option<&'a str> {
+        let item = self.precursor_item?;
+        let value = spectrum.scan_item(item)?;
+        parse_scan_item_number(item, value)
+            .is_some_and(|mz| mz > 0.0)
+            .then_some(value)
+    }
+}
+
+/// Write `spectra` in Mascot Generic Format
+pub fn write_mgf<'a, W: Write>(
+    spectra: impl IntoIterator<Item = &'a Spectrum>,
+    writer: &mut W,
+    options: &PeakListOptions,
+) -> io::Result<()> {
+    for spectrum in spectra {
+        writeln!(writer, "BEGIN IONS")?;
+        writeln!(writer, "TITLE={}", options.title_for(spectrum))?;
+        writeln!(writer, "RTINSECONDS={}", spectrum.time * 60.0)?;
+        if let Some(mz) = options.precursor_mz(spectrum) {
+            writeln!(writer, "PEPMASS={mz}")?;
+            if let Some(z) = options.precursor_charge {
+                let sign = if z < 0 { '-' } else { '+' };
+                writeln!(writer, "CHARGE={}{sign}", z.abs())?;
+            }
+        }
+        for (name, item) in options.extra_fields.iter() {
+            if let Some(value) = spectrum.scan_item(*item) {
+                writeln!(writer, "{name}={value}")?;
+            }
+        }
+        for (mz, intensity) in spectrum
+            .mz_array
+            .iter()
+            .zip(spectrum.intensity_array.iter())
+        {
+            writeln!(writer, "{mz} {intensity}")?;
+        }
+        writeln!(writer, "END IONS")?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Write `spectra` in NIST MSP format
+pub fn write_msp<'a, W: Write>(
+    spectra: impl IntoIterator<Item = &'a Spectrum>,
+    writer: &mut W,
+    options: &PeakListOptions,
+) -> io::Result<()> {
+    for spectrum in spectra {
+        writeln!(writer, "Name: {}", options.title_for(spectrum))?;
+        writeln!(writer, "RetentionTime: {}", spectrum.time)?;
+        if let Some(mz) = options.precursor_mz(spectrum) {
+            writeln!(writer, "PrecursorMZ: {mz}")?;
+            if let Some(z) = options.precursor_charge {
+                writeln!(writer, "Charge: {z}")?;
+            }
+        }
+        if let Some(drift_time) = spectrum.drift_time {
+            writeln!(writer, "DriftTime: {drift_time}")?;
+        }
+        for (name, item) in options.extra_fields.iter() {
+            if let Some(value) = spectrum.scan_item(*item) {
+                writeln!(writer, "{name}: {value}")?;
+            }
+        }
+        let n_peaks = spectrum.mz_array.len().min(spectrum.intensity_array.len());
+        writeln!(writer, "Num Peaks: {n_peaks}")?;
+        for (mz, intensity) in spectrum
+            .mz_array
+            .iter()
+            .zip(spectrum.intensity_array.iter())
+        {
+            writeln!(writer, "{mz}\t{intensity}")?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Controls how two-column delimited text is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DelimitedOptions {
+    /// The character between columns
+    pub delimiter: char,
+    /// Whether to write a header row first
+    pub header: bool,
+}
+
+impl Default for DelimitedOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            header: true,
+        }
+    }
+}
+
+impl DelimitedOptions {
+    pub fn new(delimiter: char, header: bool) -> Self {
+        Self { delimiter, header }
+    }
+
+    /// Comma-separated values with a header row
+    pub fn csv() -> Self {
+        Self::default()
+    }
+
+    /// Tab-separated values with a header row
+    pub fn tsv() -> Self {
+        Self::new('\t', true)
+    }
+}
+
+/// Write paired `xs` and `ys` as two delimited columns named by `header`, e.g. the time and
+/// intensity arrays of a chromatogram
+pub fn write_delimited_pairs<W: Write + ?Sized>(
+    writer: &mut W,
+    header: [&str; 2],
+    xs: &[f32],
+    ys: &[f32],
+    options: &DelimitedOptions,
+) -> io::Result<()> {
+    let delim = options.delimiter;
+    if options.header {
+        writeln!(writer, "{}{delim}{}", header[0], header[1])?;
+    }
+    for (x, y) in xs.iter().zip(ys) {
+        writeln!(writer, "{x}{delim}{y}")?;
+    }
+    Ok(())
+}
+
+impl Spectrum {
+    /// Write the spectrum's `mz` and `intensity` arrays as delimited text
+    pub fn to_csv<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        options: &DelimitedOptions,
+    ) -> io::Result<()> {
+        write_delimited_pairs(
+            writer,
+            ["mz", "intensity"],
+            &self.mz_array,
+            &self.intensity_array,
+            options,
+        )
+    }
+}
+
+impl Trace {
+    /// Write the trace's `time` and `intensity` arrays as delimited text
+    pub fn to_csv<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        options: &DelimitedOptions,
+    ) -> io::Result<()> {
+        write_delimited_pairs(
+            writer,
+            ["time", "intensity"],
+            &self.time,
+            &self.intensity,
+            options,
+        )
+    }
+}
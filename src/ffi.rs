@@ -109,7 +109,7 @@ extern "stdcall" {
         ccs: c_float,
         mass: c_float,
         charge: c_int,
-        driftTime: *const c_float,
+        driftTime: *mut c_float,
     ) -> c_int;
     pub fn getCollisionalCrossSection(
         mlInfoReader: CMassLynxBaseReader,
@@ -242,7 +242,7 @@ extern "stdcall" {
         pOffset: *const c_int,
     ) -> c_int;
 
-    pub fn createParameters(mlParameters: *const CMassLynxParameters) -> c_int;
+    pub fn createParameters(mlParameters: *mut CMassLynxParameters) -> c_int;
     pub fn createParametersFromParameters(
         mlSourceParameters: CMassLynxParameters,
         mlParameters: *mut CMassLynxParameters,
@@ -328,7 +328,7 @@ extern "stdcall" {
 
     // Base processor
     pub fn createRawProcessor(
-        mlRawProcessor: *const CMassLynxBaseProcessor,
+        mlRawProcessor: *mut CMassLynxBaseProcessor,
         nType: MassLynxBaseType,
         pCallback: ProgressCallBack,
         pCaller: *const c_void,
@@ -363,6 +363,11 @@ extern "stdcall" {
         mlLockMassProcessor: CMassLynxBaseProcessor,
         pApplied: *const c_char,
     ) -> c_int;
+    pub fn autoLockMassCorrect(
+        mlLockMassProcessor: CMassLynxBaseProcessor,
+        bForce: c_char,
+        pApplied: *mut c_char,
+    ) -> c_int;
     pub fn removeLockMassCorrection(mlLockMassProcessor: CMassLynxBaseProcessor) -> c_int;
     pub fn getLockMassCandidates(
         mlLockMassProcessor: CMassLynxBaseProcessor,
@@ -466,3 +471,32 @@ extern "stdcall" {
         pParameters: CMassLynxParameters,
     ) -> c_int;
 }
+
+// The Win32 calls used to hand paths to the SDK in the ANSI code page it expects
+#[cfg(windows)]
+pub const CP_ACP: c_uint = 0;
+#[cfg(windows)]
+pub const CP_UTF8: c_uint = 65001;
+#[cfg(windows)]
+pub const WC_NO_BEST_FIT_CHARS: c_uint = 0x400;
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn GetACP() -> c_uint;
+    pub fn WideCharToMultiByte(
+        CodePage: c_uint,
+        dwFlags: c_uint,
+        lpWideCharStr: *const u16,
+        cchWideChar: c_int,
+        lpMultiByteStr: *mut c_char,
+        cbMultiByte: c_int,
+        lpDefaultChar: *const c_char,
+        lpUsedDefaultChar: *mut c_int,
+    ) -> c_int;
+    pub fn GetShortPathNameW(
+        lpszLongPath: *const u16,
+        lpszShortPath: *mut u16,
+        cchBuffer: c_uint,
+    ) -> c_uint;
+}
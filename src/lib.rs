@@ -1,21 +1,40 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
 pub mod base;
 pub mod constants;
+pub mod export;
 mod ffi;
+pub mod metadata;
+pub mod mobility;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "parquet")]
+pub mod parquet;
 pub mod reader;
+pub mod watch;
 
 pub use base::{
     get_mass_lynx_version, AsMassLynxSource, MassLynxAnalogReader, MassLynxChromatogramReader,
-    MassLynxError, MassLynxInfoReader, MassLynxLockMassProcessor, MassLynxParameters,
-    MassLynxResult, MassLynxScanProcessor, MassLynxScanReader,
+    MassLynxError, MassLynxErrorKind, MassLynxInfoReader, MassLynxLockMassProcessor,
+    MassLynxParameters, MassLynxResult, MassLynxScanProcessor, MassLynxScanReader,
 };
 
 pub use constants::{
     AcquisitionParameter,
     AnalogParameter,
     AnalogTraceType,
+    AutoLynxSettings,
+    AutoLynxStatus,
     CentroidParameter,
     DDAIsolationWindowParameter,
+    DDAParameter,
+    Ionization,
+    ItemKey,
+    LockMassCompound,
     MassLynxHeaderItem,
     MassLynxIonMode,
     MassLynxScanItem,
+    MassLynxStatusType,
+    MassLynxUnit,
+    Polarity,
 };
\ No newline at end of file
@@ -1,64 +1,479 @@
-use std::env;
-use masslynx::reader::MassLynxReader;
-use masslynx::{self, MassLynxError, MassLynxResult};
-
-#[allow(unused)]
-fn show_spectrum(reader: &mut MassLynxReader) {
-    let spectrum_idx = env::args()
-        .skip(2)
-        .next()
-        .and_then(|s| s.parse::<usize>().ok())
-        .unwrap_or_default();
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-    // This may panic if the index is out of bounds
-    let spec = match reader.get_spectrum(spectrum_idx) {
-        Some(s) => s,
-        None => panic!("Index {} out of bounds for file {:?} with {} spectra", spectrum_idx, reader.path(), reader.len()),
-    };
-    eprintln!("{:?}", spec);
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use masslynx::export::{
+    write_delimited_pairs, write_mgf, write_msp, DelimitedOptions, PeakListOptions,
+};
+use masslynx::reader::{LockMassSettings, MassLynxReader};
+use masslynx::{self, LockMassCompound, MassLynxResult, MassLynxScanItem};
+use serde_json::json;
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+/// Read Waters MassLynx RAW directories
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
 }
 
-#[allow(unused)]
-fn show_cycle(reader: &mut MassLynxReader) {
-    let spectrum_idx = env::args()
-        .skip(2)
-        .next()
-        .and_then(|s| s.parse::<usize>().ok())
-        .unwrap_or_default();
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Summarize the contents of a RAW directory
+    Info(InfoArgs),
+    /// Describe the run's header, acquisition parameters, functions and analog channels
+    Meta(MetaArgs),
+    /// List the scan functions of a run
+    Functions(FunctionsArgs),
+    /// List the scan items recorded for each function, or their values for a range of scans
+    ScanItems(ScanItemsArgs),
+    /// Compare the header items reported by the SDK with those in `_HEADER.TXT`
+    Header(HeaderArgs),
+    /// Check a RAW directory for missing, corrupt or truncated data
+    Validate(ValidateArgs),
+    /// Summarize the quality of a run
+    Qc(QcArgs),
+    /// Write the total ion chromatogram
+    Tic(ChromatogramArgs),
+    /// Write the base peak intensity chromatogram
+    Bpi(ChromatogramArgs),
+    /// Write extracted ion chromatograms as CSV, or as Arrow IPC with the `arrow` feature
+    Xic(XicArgs),
+    /// Write the m/z and intensity arrays of a spectrum
+    Spectrum(SpectrumArgs),
+    /// Write the drift scans of an ion mobility cycle
+    Cycle(CycleArgs),
+    /// Write a mobilogram over a retention time and m/z range
+    Mobilogram(MobilogramArgs),
+    /// Write the analog traces
+    Analog(AnalogArgs),
+    /// Export every analog channel as CSV, aligned to the MS time axis
+    ExportAnalog(ExportAnalogArgs),
+    /// Convert between drift times and collisional cross sections for lists of ions
+    Ccs(CcsArgs),
+    /// Follow a run that is still being acquired, writing new TIC points or spectra as they appear
+    Watch(WatchArgs),
+    /// Inspect, apply or remove the lock mass correction
+    Lockmass(LockmassArgs),
+    /// Run another subcommand over many RAW directories
+    Batch(BatchArgs),
+}
 
-    // This may panic if the index is out of bounds
-    match reader.get_cycle(spectrum_idx) {
-        Some(spec) => {
-            eprintln!("{:?}", spec);
-        },
-        None => {
-            match reader.cycle_index().get(spectrum_idx) {
-                Some(c) => {
-                    if !c.has_drift_time() {
-                        eprintln!("Cycle {spectrum_idx} has no ion mobility");
-                    } else {
-                        panic!("Index {} out of bounds for file {:?} with {} cycles", spectrum_idx, reader.path(), reader.cycle_index().len())
-                    }
-                },
-                None => panic!("Index {} out of bounds for file {:?} with {} cycles", spectrum_idx, reader.path(), reader.cycle_index().len())
-            }
-        },
-    };
+#[derive(Debug, Args)]
+struct OutputArgs {
+    /// Where to write the output. Defaults to STDOUT
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl OutputArgs {
+    fn open(&self) -> io::Result<Box<dyn Write>> {
+        match self.output.as_ref() {
+            Some(path) => Ok(Box::new(io::BufWriter::new(fs::File::create(path)?))),
+            None => Ok(Box::new(io::BufWriter::new(io::stdout().lock()))),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct CcsArgs {
+    /// The path to the RAW directory holding the CCS calibration
+    path: PathBuf,
+
+    /// CSV or TSV files of ions with `mz`, an optional `charge`, and either `drift_time` or
+    /// `ccs` columns
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// The direction to convert in
+    #[arg(long, value_enum, default_value_t = CcsDirection::DriftToCcs)]
+    direction: CcsDirection,
+
+    /// The charge to use for ions without a `charge` column
+    #[arg(short = 'z', long, default_value_t = 1)]
+    charge: i32,
+
+    /// A CSV or TSV file of reference ions with `mz` and optional `name` and `charge` columns.
+    /// Each input ion is matched to the closest reference within `--ppm`
+    #[arg(long)]
+    references: Option<PathBuf>,
+
+    /// The mass error tolerance for matching input ions to references, in parts per million
+    #[arg(long, default_value_t = 10.0, requires = "references")]
+    ppm: f64,
+
+    #[command(flatten)]
+    output: OutputArgs,
 }
 
-#[allow(unused)]
-fn show_chromatogram(reader: &mut MassLynxReader) {
-    let mass = env::args()
-        .skip(2)
-        .next()
-        .and_then(|s| s.parse::<f32>().ok())
-        .unwrap_or(366.14);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CcsDirection {
+    /// Compute CCS values from a `drift_time` column
+    DriftToCcs,
+    /// Compute drift times from a `ccs` column
+    CcsToDrift,
+}
+
+#[derive(Debug, Args)]
+struct WatchArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// How often to check for new data, in seconds
+    #[arg(short, long, default_value = "5", value_parser = parse_interval)]
+    interval: Duration,
 
-    let (time, ints) = reader.read_xic(0, mass, 0.2, false).unwrap();
+    /// Stop after this many consecutive checks find no new data. Runs until interrupted if
+    /// not given
+    #[arg(long)]
+    idle_limit: Option<usize>,
 
-    time.into_iter().zip(ints).for_each(|(t, i)| {
-        eprintln!("{t}\t{i}");
-    });
+    /// Write the peaks of each new spectrum instead of its total ion current
+    #[arg(short, long)]
+    spectra: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+#[command(group = ArgGroup::new("target").args(["apply", "compound"]))]
+struct LockmassArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// Apply a lock mass correction using this reference m/z
+    #[arg(short, long, conflicts_with = "remove")]
+    apply: Option<f32>,
+
+    /// Apply a lock mass correction using the standard reference ion of this compound, e.g.
+    /// leucine-enkephalin, in the lock mass function's polarity
+    #[arg(short, long, conflicts_with_all = ["apply", "remove"])]
+    compound: Option<LockMassCompound>,
+
+    /// The m/z tolerance used to find the reference peak when applying a correction
+    #[arg(short, long, requires = "target")]
+    tolerance: Option<f32>,
+
+    /// Remove the lock mass correction
+    #[arg(short, long)]
+    remove: bool,
+
+    /// Report the correction gain at these retention times, in minutes
+    #[arg(short, long, num_args = 1..)]
+    gain_at: Vec<f32>,
+}
+
+#[derive(Debug, Args)]
+struct BatchArgs {
+    /// The RAW directories to process, or glob patterns matching them
+    #[arg(required = true)]
+    inputs: Vec<String>,
+
+    /// Keep processing the remaining runs when one fails
+    #[arg(long)]
+    continue_on_error: bool,
+
+    /// The subcommand and its arguments, without the RAW path. `{stem}` in an argument is
+    /// replaced by the name of each run, e.g. `-- tic -o {stem}.tic.tsv`
+    #[arg(last = true, required = true)]
+    command: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+struct InfoArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct MetaArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// Write the metadata as JSON
+    #[arg(long)]
+    json: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+struct FunctionsArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+struct ScanItemsArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// Only describe this function
+    #[arg(short, long)]
+    function: Option<usize>,
+
+    /// Write the values of the scan items for scans starting at this one, instead of listing them
+    #[arg(short, long)]
+    scan: Option<usize>,
+
+    /// The last scan to write values for. Defaults to `--scan`
+    #[arg(short, long, requires = "scan")]
+    end_scan: Option<usize>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+struct HeaderArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// Only show entries where the SDK and the header file disagree
+    #[arg(long)]
+    conflicts: bool,
+
+    /// Write the headers as JSON
+    #[arg(long)]
+    json: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+struct ValidateArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// The number of evenly spaced scans to try reading from each function
+    #[arg(short, long, default_value_t = 5)]
+    sample: usize,
+}
+
+#[derive(Debug, Args)]
+struct QcArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// The number of evenly spaced retention times to sample the lock mass correction at
+    #[arg(long, default_value_t = 10)]
+    lockmass_points: usize,
+
+    /// Write the report as JSON
+    #[arg(long)]
+    json: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+struct ChromatogramArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// Only read the chromatogram of this function instead of the whole run
+    #[arg(short, long)]
+    function: Option<usize>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+struct XicArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// The m/z to extract
+    #[arg(
+        short,
+        long,
+        required_unless_present = "targets",
+        conflicts_with = "targets"
+    )]
+    mz: Option<f32>,
+
+    /// A CSV or TSV file of targets to extract, with an `mz` column and optional `name`,
+    /// `tolerance`, `function`, `rt_start` and `rt_end` columns
+    #[arg(short, long)]
+    targets: Option<PathBuf>,
+
+    /// The width of the m/z window to extract, unless a target specifies its own `tolerance`
+    #[arg(short = 'w', long, default_value_t = 0.2)]
+    mass_window: f32,
+
+    /// The function to extract from, unless a target specifies its own `function`
+    #[arg(short, long, default_value_t = 0)]
+    function: usize,
+
+    /// Extract from product ion spectra
+    #[arg(short, long)]
+    daughters: bool,
+
+    /// Write an Arrow IPC file instead of CSV
+    #[cfg(feature = "arrow")]
+    #[arg(long)]
+    arrow: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+struct SpectrumArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// The index of the spectrum to read
+    index: usize,
+
+    /// Centroid profile spectra before writing them
+    #[arg(short, long)]
+    centroid: bool,
+
+    /// The format to write the spectrum in
+    #[arg(long, value_enum, default_value_t = SpectrumFormat::Tsv)]
+    format: SpectrumFormat,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SpectrumFormat {
+    /// Tab-separated m/z and intensity columns
+    Tsv,
+    /// Mascot Generic Format
+    Mgf,
+    /// NIST MSP
+    Msp,
+}
+
+#[derive(Debug, Args)]
+struct CycleArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// The index of the cycle to read
+    index: usize,
+
+    /// Centroid profile drift scans before writing them
+    #[arg(short, long)]
+    centroid: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+struct MobilogramArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// The function to read from
+    #[arg(short, long, default_value_t = 0)]
+    function: usize,
+
+    /// The first scan to include, instead of `--start-time`
+    #[arg(long, conflicts_with_all = ["start_time", "end_time"])]
+    start_scan: Option<usize>,
+
+    /// The last scan to include, instead of `--end-time`
+    #[arg(long, conflicts_with_all = ["start_time", "end_time"])]
+    end_scan: Option<usize>,
+
+    /// The earliest retention time to include, in minutes
+    #[arg(long)]
+    start_time: Option<f64>,
+
+    /// The latest retention time to include, in minutes
+    #[arg(long)]
+    end_time: Option<f64>,
+
+    /// The lowest m/z to include
+    #[arg(long)]
+    start_mass: f32,
+
+    /// The highest m/z to include
+    #[arg(long)]
+    end_mass: f32,
+
+    /// The mobility axis to write
+    #[arg(short, long, value_enum, default_value_t = MobilityAxis::DriftTime)]
+    axis: MobilityAxis,
+
+    /// The charge state used to compute CCS values
+    #[arg(short = 'z', long, default_value_t = 1)]
+    charge: i32,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// The unit of the mobility axis of a mobilogram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MobilityAxis {
+    /// The drift bin index
+    Bin,
+    /// The drift time in milliseconds
+    DriftTime,
+    /// The collisional cross section in square Ångström, computed at the centre of the m/z range
+    Ccs,
+}
+
+#[derive(Debug, Args)]
+struct AnalogArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// Only list the analog traces, without their signal
+    #[arg(short, long)]
+    list: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+struct ExportAnalogArgs {
+    /// The path to the RAW directory
+    path: PathBuf,
+
+    /// Write each channel to its own CSV file in this directory instead of a single table
+    #[arg(short = 'd', long, conflicts_with = "output")]
+    output_dir: Option<PathBuf>,
+
+    /// Do not apply the channel time offsets recorded in the acquisition header
+    #[arg(long)]
+    no_offset: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+fn open_reader(path: &Path) -> MassLynxResult<MassLynxReader> {
+    log::info!("Opening {}", path.display());
+    let reader = MassLynxReader::from_path(&path.to_string_lossy())?;
+    log::info!("Opened reader with {} spectra", reader.len());
+    Ok(reader)
+}
+
+fn write_pairs(out: &mut dyn Write, header: [&str; 2], xs: &[f32], ys: &[f32]) -> io::Result<()> {
+    write_delimited_pairs(out, header, xs, ys, &DelimitedOptions::tsv())?;
+    out.flush()
 }
 
 fn show_ms_level_counts(reader: &mut MassLynxReader) {
@@ -68,8 +483,9 @@ fn show_ms_level_counts(reader: &mut MassLynxReader) {
     for cycle in reader.iter_cycles() {
         counters[funcs[cycle.function()].ms_level as usize] += 1;
     }
+    reader.set_signal_loading(true);
 
-    eprintln!("MS Levels: {counters:?}");
+    println!("MS Levels: {counters:?}");
 }
 
 fn show_tic(reader: &mut MassLynxReader) -> MassLynxResult<()> {
@@ -81,7 +497,7 @@ fn show_tic(reader: &mut MassLynxReader) -> MassLynxResult<()> {
         .max_by(|(_, a), (_, b)| a.total_cmp(b))
         .unwrap_or_default();
 
-    eprintln!(
+    println!(
         "TIC from {:0.2} to {:0.2} has maximum at {:0.2} with intensity {tic_max:0.2e}",
         tic_time.first().copied().unwrap_or_default(),
         tic_time.last().copied().unwrap_or_default(),
@@ -90,46 +506,1274 @@ fn show_tic(reader: &mut MassLynxReader) -> MassLynxResult<()> {
     Ok(())
 }
 
-#[allow(unused)]
-fn show_analog(reader: &mut MassLynxReader) -> MassLynxResult<()> {
-    for trace in reader.iter_analogs() {
-        eprintln!("{} {}: {}", trace.name, trace.unit, trace.time.len());
+fn info(args: InfoArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    println!(
+        "MassLynx Version: {}",
+        masslynx::get_mass_lynx_version().unwrap_or_default()
+    );
+    println!("Path: {}", reader.path().display());
+    println!("Spectra: {}", reader.len());
+    println!("Cycles: {}", reader.cycle_index().len());
+    println!("Functions: {}", reader.functions().len());
+    for (item, value) in reader.header_items()? {
+        println!("{item}: {value}");
+    }
+    show_ms_level_counts(&mut reader);
+    show_tic(&mut reader)?;
+    Ok(())
+}
+
+fn run_metadata(reader: &mut MassLynxReader) -> CliResult<serde_json::Value> {
+    let header: serde_json::Map<_, _> = reader
+        .header_items()?
+        .into_iter()
+        .map(|(item, value)| (format!("{item:?}"), json!(value)))
+        .collect();
+
+    let mut acquisition: Vec<_> = reader
+        .acquisition_information_with_unknown()?
+        .into_iter()
+        .map(|(param, value)| (param.to_string(), json!(value)))
+        .collect();
+    acquisition.sort_by(|a, b| a.0.cmp(&b.0));
+    let acquisition: serde_json::Map<_, _> = acquisition.into_iter().collect();
+
+    let mut functions = Vec::new();
+    for func in reader.functions().to_vec() {
+        let mass_range = reader.acquisition_mass_range(func.function).ok();
+        let time_range = reader.acquisition_time_range(func.function).ok();
+        functions.push(json!({
+            "function": func.function,
+            "type": format!("{:?}", func.ftype),
+            "ms_level": func.ms_level,
+            "is_lockmass": func.is_lockmass,
+            "scan_count": func.scan_count,
+            "ion_mobility_block_size": func.ion_mobility_block_size,
+            "mass_range": mass_range,
+            "time_range": time_range,
+        }));
+    }
+
+    let offsets: Vec<_> = (0..reader.analog_trace_count())
+        .map(|i| reader.analog_time_offset(i))
+        .collect();
+    let analog: Vec<_> = reader
+        .iter_analogs()
+        .enumerate()
+        .map(|(i, trace)| {
+            json!({
+                "index": i,
+                "name": trace.name,
+                "unit": trace.unit,
+                "points": trace.time.len(),
+                "offset": offsets.get(i).copied().flatten(),
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "path": reader.path().display().to_string(),
+        "masslynx_version": masslynx::get_mass_lynx_version(),
+        "spectra": reader.len(),
+        "cycles": reader.cycle_index().len(),
+        "header": header,
+        "acquisition": acquisition,
+        "functions": functions,
+        "lockmass": {
+            "function": reader.get_lock_mass_function(),
+            "corrected": reader.is_lock_mass_corrected(),
+        },
+        "analog": analog,
+    }))
+}
+
+/// Write a JSON value as indented `key: value` lines
+fn write_json_as_text(
+    out: &mut dyn Write,
+    value: &serde_json::Value,
+    depth: usize,
+) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                if value.is_object() || value.is_array() {
+                    writeln!(out, "{indent}{key}:")?;
+                    write_json_as_text(out, value, depth + 1)?;
+                } else {
+                    writeln!(out, "{indent}{key}: {value}")?;
+                }
+            }
+        }
+        serde_json::Value::Array(values) if values.iter().all(|v| !v.is_object()) => {
+            writeln!(out, "{indent}{value}")?;
+        }
+        serde_json::Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                writeln!(out, "{indent}- {i}:")?;
+                write_json_as_text(out, value, depth + 1)?;
+            }
+        }
+        value => writeln!(out, "{indent}{value}")?,
     }
     Ok(())
 }
 
-fn show_mobilogram(reader: &mut MassLynxReader) -> MassLynxResult<()> {
-    let (time_array, intensity_array) = reader.read_mobilogram(
-        0, 0, 10, 50.0, 200.0)?;
-    eprintln!("Mobilogram from {:0.2} to {:0.2} with intensity range from {:0.2} to {:0.2},
-    ",
-    time_array.first().copied().unwrap_or_default(),
-    time_array.last().copied().unwrap_or_default(),
-    intensity_array.first().copied().unwrap_or_default(),
-    intensity_array.last().copied().unwrap_or_default()
-);
+fn meta(args: MetaArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    let metadata = run_metadata(&mut reader)?;
+    let mut out = args.output.open()?;
+    if args.json {
+        serde_json::to_writer_pretty(&mut out, &metadata)?;
+        writeln!(out)?;
+    } else {
+        write_json_as_text(&mut out, &metadata, 0)?;
+    }
+    out.flush()?;
     Ok(())
 }
 
-fn main() -> Result<(), MassLynxError> {
-    pretty_env_logger::init_timed();
-    let version = masslynx::get_mass_lynx_version();
-    eprintln!("Using MassLynx Version: {:?}", version);
-    let path = env::args().skip(1).next().unwrap();
+fn functions(args: FunctionsArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    let mut out = args.output.open()?;
+    writeln!(
+        out,
+        "function\ttype\tion_mode\tpolarity\tcontinuum\tms_level\tscans\tlow_mass\thigh_mass\tstart_time\tend_time\tim_block_size\tsonar\tlockmass"
+    )?;
+    for func in reader.functions().to_vec() {
+        let ftype = reader
+            .function_type_string(func.function)
+            .unwrap_or_else(|_| func.ftype.to_string());
+        let ion_mode = reader.ion_mode_string(func.function)?;
+        let polarity = reader.ion_mode(func.function)?.polarity();
+        let continuum = reader.is_continuum(func.function)?;
+        let (low_mass, high_mass) = reader.acquisition_mass_range(func.function)?;
+        let (start_time, end_time) = reader.acquisition_time_range(func.function)?;
+        writeln!(
+            out,
+            "{}\t{ftype}\t{ion_mode}\t{polarity:?}\t{continuum}\t{}\t{}\t{low_mass}\t{high_mass}\t{start_time}\t{end_time}\t{}\t{}\t{}",
+            func.function,
+            func.ms_level,
+            func.scan_count,
+            func.ion_mobility_block_size,
+            func.is_sonar(),
+            func.is_lockmass,
+        )?;
+    }
+    out.flush()?;
+    Ok(())
+}
 
-    eprintln!("Opening {path}");
-    let mut reader = MassLynxReader::from_path(&path)?;
-    eprintln!("Opened reader with {} spectra", reader.len());
+/// The symbol of a scan item's unit, empty if its unit is not known
+fn unit_symbol(item: MassLynxScanItem) -> &'static str {
+    item.unit().map(|unit| unit.symbol()).unwrap_or_default()
+}
 
-    eprintln!("{:?}", reader.header_items().unwrap());
-    show_ms_level_counts(&mut reader);
-    // show_analog(&mut reader)?;
-    // show_spectrum(&mut reader);
-    // show_cycle(&mut reader);
-    show_chromatogram(&mut reader);
-    if let Err(e) = show_mobilogram(&mut reader) {
-        eprintln!("No mobilogram read: {e}");
+fn scan_items(args: ScanItemsArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    let functions: Vec<usize> = match args.function {
+        Some(f) => vec![f],
+        None => reader.functions().iter().map(|f| f.function).collect(),
+    };
+    let mut out = args.output.open()?;
+    match args.scan {
+        None => {
+            writeln!(out, "function\titem\tname\tunit")?;
+            for f in functions {
+                for (item, name) in reader.scan_item_names(f)? {
+                    writeln!(out, "{f}\t{item:?}\t{name}\t{}", unit_symbol(item))?;
+                }
+            }
+        }
+        Some(start) => {
+            let end = args.end_scan.unwrap_or(start);
+            writeln!(out, "function\tscan\titem\tname\tvalue\tunit")?;
+            for f in functions {
+                let names: HashMap<_, _> = reader.scan_item_names(f)?.into_iter().collect();
+                let scan_count = reader.functions()[f].scan_count;
+                for scan in start..=end.min(scan_count.saturating_sub(1)) {
+                    for (item, value) in reader.read_scan_items(f, scan)? {
+                        let name = names.get(&item).map(|s| s.as_str()).unwrap_or_default();
+                        writeln!(
+                            out,
+                            "{f}\t{scan}\t{item:?}\t{name}\t{value}\t{}",
+                            unit_symbol(item)
+                        )?;
+                    }
+                }
+            }
+        }
     }
-    show_tic(&mut reader)?;
+    out.flush()?;
+    Ok(())
+}
+
+fn header(args: HeaderArgs) -> CliResult<()> {
+    let reader = open_reader(&args.path)?;
+    let entries: Vec<_> = reader
+        .merged_headers()?
+        .into_iter()
+        .filter(|e| !args.conflicts || e.is_conflicting())
+        .collect();
+    let mut out = args.output.open()?;
+    if args.json {
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|e| {
+                json!({
+                    "key": e.key,
+                    "item": e.item.map(|i| format!("{i:?}")),
+                    "sdk": e.sdk_value,
+                    "file": e.file_value,
+                    "conflict": e.is_conflicting(),
+                })
+            })
+            .collect();
+        serde_json::to_writer_pretty(&mut out, &entries)?;
+        writeln!(out)?;
+    } else {
+        writeln!(out, "key\tsdk\tfile\tconflict")?;
+        for e in entries.iter() {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                e.key,
+                e.sdk_value.as_deref().unwrap_or_default(),
+                e.file_value.as_deref().unwrap_or_default(),
+                if e.is_conflicting() { "*" } else { "" }
+            )?;
+        }
+    }
+    out.flush()?;
     Ok(())
 }
+
+fn validate(args: ValidateArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    reader.set_lockmass_skipping(false);
+
+    let mut problems: Vec<(Option<usize>, String)> = reader
+        .check_files()
+        .into_iter()
+        .map(|issue| (issue.function, issue.message))
+        .collect();
+
+    for func in reader.functions().to_vec() {
+        if func.ms_level == 0 {
+            continue;
+        }
+        let cycles: Vec<usize> = reader
+            .cycle_index()
+            .iter()
+            .filter(|e| e.function == func.function)
+            .map(|e| e.index)
+            .collect();
+        if cycles.is_empty() {
+            continue;
+        }
+
+        // Always include the last scan the SDK reports, where truncation shows up
+        let step = (cycles.len() / args.sample.max(1)).max(1);
+        let mut sample: Vec<usize> = cycles.iter().copied().step_by(step).collect();
+        sample.push(*cycles.last().unwrap());
+        sample.dedup();
+
+        for cycle in sample {
+            let block = reader.cycle_index()[cycle].block;
+            let result = if func.has_drift_time() {
+                reader.try_get_cycle(cycle).map(|_| ())
+            } else {
+                match reader.spectrum_range_of_cycle(cycle) {
+                    Some(range) if !range.is_empty() => {
+                        reader.try_get_spectrum(range.start).map(|_| ())
+                    }
+                    _ => {
+                        problems.push((
+                            Some(func.function),
+                            format!("Scan {block} has no spectrum in the index"),
+                        ));
+                        continue;
+                    }
+                }
+            };
+            if let Err(e) = result {
+                problems.push((
+                    Some(func.function),
+                    format!(
+                        "Failed to read scan {block}, the data may be corrupt or truncated: {e}"
+                    ),
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        println!("{}: OK", args.path.display());
+        return Ok(());
+    }
+    for (function, message) in problems.iter() {
+        match function {
+            Some(f) => println!("function {f}: {message}"),
+            None => println!("{message}"),
+        }
+    }
+    Err(format!(
+        "{} problems found in {}",
+        problems.len(),
+        args.path.display()
+    )
+    .into())
+}
+
+/// Count, range, mean and relative standard deviation (in percent) of a series of values
+fn summary_statistics(values: &[f32]) -> serde_json::Value {
+    if values.is_empty() {
+        return json!({ "count": 0 });
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n;
+    let variance = values
+        .iter()
+        .map(|v| (*v as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    let rsd = if mean != 0.0 {
+        Some(variance.sqrt() / mean * 100.0)
+    } else {
+        None
+    };
+    json!({
+        "count": values.len(),
+        "min": values.iter().copied().fold(f32::INFINITY, f32::min),
+        "max": values.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+        "mean": mean,
+        "rsd": rsd,
+    })
+}
+
+fn qc_report(reader: &mut MassLynxReader, lockmass_points: usize) -> CliResult<serde_json::Value> {
+    let mut functions = Vec::new();
+    for func in reader.functions().to_vec() {
+        if func.ms_level == 0 {
+            continue;
+        }
+        let times: Vec<f64> = reader
+            .cycle_index()
+            .iter()
+            .filter(|e| e.function == func.function)
+            .map(|e| e.time)
+            .collect();
+        // Cycle times are reported in seconds
+        let cycle_times: Vec<f32> = times
+            .windows(2)
+            .map(|w| ((w[1] - w[0]) * 60.0) as f32)
+            .collect();
+        let (_, tic) = reader.tic_of(func.function)?;
+        let (_, bpi) = reader.bpi_of(func.function)?;
+        let empty_scans = tic.iter().filter(|v| **v == 0.0).count();
+        functions.push(json!({
+            "function": func.function,
+            "ms_level": func.ms_level,
+            "is_lockmass": func.is_lockmass,
+            "scan_count": func.scan_count,
+            "empty_scans": empty_scans,
+            "cycle_time": summary_statistics(&cycle_times),
+            "tic": summary_statistics(&tic),
+            "bpi": summary_statistics(&bpi),
+        }));
+    }
+
+    // The RSD of the whole-run TIC is a rough measure of spray stability
+    let (_, tic) = reader.tic()?;
+    let spray_stability = summary_statistics(&tic);
+
+    let lockmass = if reader.is_lock_mass_corrected() && lockmass_points > 0 {
+        let start = reader
+            .cycle_index()
+            .first()
+            .map(|e| e.time)
+            .unwrap_or_default();
+        let end = reader
+            .cycle_index()
+            .last()
+            .map(|e| e.time)
+            .unwrap_or_default();
+        let step = if lockmass_points > 1 {
+            (end - start) / (lockmass_points - 1) as f64
+        } else {
+            0.0
+        };
+        let mut gains = Vec::with_capacity(lockmass_points);
+        let mut samples = Vec::with_capacity(lockmass_points);
+        for i in 0..lockmass_points {
+            let time = (start + step * i as f64) as f32;
+            let gain = reader.lock_mass_gain(time)?;
+            gains.push(gain);
+            samples.push(json!({ "time": time, "gain": gain }));
+        }
+        json!({
+            "corrected": true,
+            "gain": summary_statistics(&gains),
+            "samples": samples,
+        })
+    } else {
+        json!({ "corrected": reader.is_lock_mass_corrected() })
+    };
+
+    let analog: Vec<_> = reader
+        .iter_analogs()
+        .map(|trace| {
+            json!({
+                "name": trace.name,
+                "unit": trace.unit,
+                "values": summary_statistics(&trace.intensity),
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "path": reader.path().display().to_string(),
+        "functions": functions,
+        "spray_stability": spray_stability,
+        "lockmass": lockmass,
+        "analog": analog,
+    }))
+}
+
+fn qc(args: QcArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    let report = qc_report(&mut reader, args.lockmass_points)?;
+    let mut out = args.output.open()?;
+    if args.json {
+        serde_json::to_writer_pretty(&mut out, &report)?;
+        writeln!(out)?;
+    } else {
+        write_json_as_text(&mut out, &report, 0)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn chromatogram(args: ChromatogramArgs, bpi: bool) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    let (time, intensity) = match (args.function, bpi) {
+        (Some(f), false) => reader.tic_of(f)?,
+        (Some(f), true) => reader.bpi_of(f)?,
+        (None, false) => reader.tic()?,
+        (None, true) => reader.bpi()?,
+    };
+    let mut out = args.output.open()?;
+    write_pairs(&mut out, ["time", "intensity"], &time, &intensity)?;
+    Ok(())
+}
+
+/// A single extraction target read from an XIC target list
+#[derive(Debug, Clone)]
+struct XicTarget {
+    name: String,
+    mz: f32,
+    mass_window: f32,
+    function: usize,
+    time_range: Option<(f32, f32)>,
+}
+
+impl XicTarget {
+    fn contains_time(&self, time: f32) -> bool {
+        self.time_range
+            .map(|(start, end)| start <= time && time <= end)
+            .unwrap_or(true)
+    }
+}
+
+/// Open a CSV or TSV file with a header row, inferring the delimiter from the header line
+fn open_delimited(path: &Path) -> CliResult<csv::Reader<io::Cursor<Vec<u8>>>> {
+    let content = fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let delimiter = match content.split(|b| *b == b'\n').next() {
+        Some(header) if header.contains(&b'\t') => b'\t',
+        _ => b',',
+    };
+    Ok(csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .trim(csv::Trim::All)
+        .from_reader(io::Cursor::new(content)))
+}
+
+/// Read extraction targets from a CSV or TSV file
+fn read_xic_targets(
+    path: &Path,
+    default_mass_window: f32,
+    default_function: usize,
+) -> CliResult<Vec<XicTarget>> {
+    let mut reader = open_delimited(path)?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let mz_col = column("mz").ok_or_else(|| {
+        format!(
+            "The target list {} does not have an `mz` column",
+            path.display()
+        )
+    })?;
+    let name_col = column("name");
+    let tolerance_col = column("tolerance");
+    let function_col = column("function");
+    let rt_start_col = column("rt_start");
+    let rt_end_col = column("rt_end");
+
+    let mut targets = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).filter(|v| !v.is_empty());
+        let line = i + 2;
+
+        let mz: f32 = field(Some(mz_col))
+            .ok_or_else(|| format!("Target on line {line} has no m/z"))?
+            .parse()
+            .map_err(|e| format!("Failed to parse m/z on line {line}: {e}"))?;
+        let mass_window = match field(tolerance_col) {
+            Some(v) => v
+                .parse()
+                .map_err(|e| format!("Failed to parse tolerance on line {line}: {e}"))?,
+            None => default_mass_window,
+        };
+        let function = match field(function_col) {
+            Some(v) => v
+                .parse()
+                .map_err(|e| format!("Failed to parse function on line {line}: {e}"))?,
+            None => default_function,
+        };
+        let rt_start: Option<f32> = field(rt_start_col)
+            .map(|v| v.parse())
+            .transpose()
+            .map_err(|e| format!("Failed to parse rt_start on line {line}: {e}"))?;
+        let rt_end: Option<f32> = field(rt_end_col)
+            .map(|v| v.parse())
+            .transpose()
+            .map_err(|e| format!("Failed to parse rt_end on line {line}: {e}"))?;
+        let time_range = match (rt_start, rt_end) {
+            (None, None) => None,
+            (start, end) => Some((
+                start.unwrap_or(f32::NEG_INFINITY),
+                end.unwrap_or(f32::INFINITY),
+            )),
+        };
+        let name = field(name_col)
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| mz.to_string());
+
+        targets.push(XicTarget {
+            name,
+            mz,
+            mass_window,
+            function,
+            time_range,
+        });
+    }
+    Ok(targets)
+}
+
+fn xic(args: XicArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    let targets_path = match (args.mz, args.targets.as_ref()) {
+        (Some(mz), _) => {
+            let (time, intensity) =
+                reader.read_xic(args.function, mz, args.mass_window, args.daughters)?;
+            let mut out = args.output.open()?;
+            #[cfg(feature = "arrow")]
+            if args.arrow {
+                let batch = masslynx::arrow::chromatogram_to_record_batch(&time, &intensity)?;
+                masslynx::arrow::write_ipc(&mut out, &batch)?;
+                out.flush()?;
+                return Ok(());
+            }
+            let options = DelimitedOptions::csv();
+            write_delimited_pairs(&mut out, ["time", "intensity"], &time, &intensity, &options)?;
+            out.flush()?;
+            return Ok(());
+        }
+        (None, Some(path)) => path,
+        (None, None) => unreachable!("clap requires either --mz or --targets"),
+    };
+
+    let targets = read_xic_targets(targets_path, args.mass_window, args.function)?;
+    log::info!("Extracting {} targets", targets.len());
+
+    // Targets sharing a function and mass window can be extracted in a single pass
+    let mut groups: Vec<((usize, u32), Vec<&XicTarget>)> = Vec::new();
+    for target in targets.iter() {
+        let key = (target.function, target.mass_window.to_bits());
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, group)) => group.push(target),
+            None => groups.push((key, vec![target])),
+        }
+    }
+
+    let mut extracted = Vec::with_capacity(targets.len());
+    for ((function, mass_window), group) in groups {
+        let masses: Vec<f32> = group.iter().map(|t| t.mz).collect();
+        let xics = reader.read_xics(
+            function,
+            &masses,
+            f32::from_bits(mass_window),
+            args.daughters,
+        )?;
+        for (target, (time, intensity)) in group.into_iter().zip(xics) {
+            let (time, intensity): (Vec<f32>, Vec<f32>) = time
+                .iter()
+                .copied()
+                .zip(intensity)
+                .filter(|(t, _)| target.contains_time(*t))
+                .unzip();
+            extracted.push((target, time, intensity));
+        }
+    }
+
+    let mut out = args.output.open()?;
+    #[cfg(feature = "arrow")]
+    if args.arrow {
+        let mut table = masslynx::arrow::XicTableBuilder::new();
+        for (target, time, intensity) in extracted.iter() {
+            table.append_chromatogram(&target.name, target.mz, target.function, time, intensity);
+        }
+        masslynx::arrow::write_ipc(&mut out, &table.finish()?)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    writeln!(out, "name,mz,function,time,intensity")?;
+    for (target, time, intensity) in extracted {
+        for (t, i) in time.iter().zip(intensity.iter()) {
+            writeln!(
+                out,
+                "{},{},{},{t},{i}",
+                csv_field(&target.name),
+                target.mz,
+                target.function
+            )?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Quote a value for a CSV field if it contains special characters
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn spectrum(args: SpectrumArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    reader.set_centroiding(args.centroid);
+    let spec = reader.try_get_spectrum(args.index)?;
+    log::info!(
+        "Spectrum {} ({}) at {:0.3} minutes",
+        spec.index,
+        spec.native_id(),
+        spec.time
+    );
+    let mut out = args.output.open()?;
+    let run_name = args
+        .path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let options = PeakListOptions::new(run_name);
+    match args.format {
+        SpectrumFormat::Tsv => spec.to_csv(&mut out, &DelimitedOptions::tsv())?,
+        SpectrumFormat::Mgf => write_mgf([&spec], &mut out, &options)?,
+        SpectrumFormat::Msp => write_msp([&spec], &mut out, &options)?,
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn cycle(args: CycleArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    reader.set_centroiding(args.centroid);
+    let cycle = reader.try_get_cycle(args.index)?;
+    if !cycle.identifier.has_drift_time() {
+        return Err(format!("Cycle {} has no ion mobility", args.index).into());
+    }
+    log::info!(
+        "Cycle {} ({}) at {:0.3} minutes",
+        cycle.index,
+        cycle.native_id(),
+        cycle.time
+    );
+    let mut out = args.output.open()?;
+    writeln!(out, "drift_time\tmz\tintensity")?;
+    for scan in cycle.signal.iter() {
+        for (mz, intensity) in scan.mz_array.iter().zip(scan.intensity_array.iter()) {
+            writeln!(out, "{}\t{mz}\t{intensity}", scan.drift_time)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn mobilogram(args: MobilogramArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    let function = reader.functions().get(args.function).ok_or_else(|| {
+        format!(
+            "Function {} does not exist, the run has {} functions",
+            args.function,
+            reader.functions().len()
+        )
+    })?;
+    if !function.has_drift_time() {
+        return Err(format!("Function {} has no ion mobility data", args.function).into());
+    }
+    let last_scan = function.scan_count.saturating_sub(1);
+
+    let (start_scan, end_scan) = if args.start_time.is_some() || args.end_time.is_some() {
+        let start_time = args.start_time.unwrap_or(f64::NEG_INFINITY);
+        let end_time = args.end_time.unwrap_or(f64::INFINITY);
+        reader
+            .scan_range_for_time(args.function, start_time, end_time)
+            .ok_or_else(|| {
+                format!(
+                    "Function {} has no scans between {start_time} and {end_time} minutes",
+                    args.function
+                )
+            })?
+    } else {
+        (
+            args.start_scan.unwrap_or_default(),
+            args.end_scan.unwrap_or(last_scan),
+        )
+    };
+
+    let (drift_bins, intensity) = reader.read_mobilogram_bins(
+        args.function,
+        start_scan,
+        end_scan,
+        args.start_mass,
+        args.end_mass,
+    )?;
+
+    let mut out = args.output.open()?;
+    let mz = (args.start_mass + args.end_mass) / 2.0;
+    match args.axis {
+        MobilityAxis::Bin => writeln!(out, "drift_bin,intensity")?,
+        MobilityAxis::DriftTime => writeln!(out, "drift_bin,drift_time,intensity")?,
+        MobilityAxis::Ccs => writeln!(out, "drift_bin,drift_time,ccs,intensity")?,
+    }
+    for (bin, intensity) in drift_bins.into_iter().zip(intensity) {
+        if args.axis == MobilityAxis::Bin {
+            writeln!(out, "{bin},{intensity}")?;
+            continue;
+        }
+        let drift_time = reader.drift_time_of(bin as usize)?;
+        if args.axis == MobilityAxis::Ccs {
+            let ccs = reader.collisional_cross_section(drift_time as f32, mz, args.charge)?;
+            writeln!(out, "{bin},{drift_time},{ccs},{intensity}")?;
+        } else {
+            writeln!(out, "{bin},{drift_time},{intensity}")?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn analog(args: AnalogArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    let mut out = args.output.open()?;
+    if args.list {
+        writeln!(out, "index\tname\tunit\tpoints")?;
+        for (i, trace) in reader.iter_analogs().enumerate() {
+            writeln!(
+                out,
+                "{i}\t{}\t{}\t{}",
+                trace.name,
+                trace.unit,
+                trace.time.len()
+            )?;
+        }
+    } else {
+        writeln!(out, "name\tunit\ttime\tintensity")?;
+        for trace in reader.iter_analogs() {
+            for (time, intensity) in trace.time.iter().zip(trace.intensity.iter()) {
+                writeln!(out, "{}\t{}\t{time}\t{intensity}", trace.name, trace.unit)?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Make an analog channel description safe to use in a file name
+fn channel_file_stem(index: usize, name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{index}_{}", name.trim_matches('_'))
+}
+
+fn export_analog(args: ExportAnalogArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    let offsets: Vec<f32> = (0..reader.analog_trace_count())
+        .map(|i| {
+            if args.no_offset {
+                0.0
+            } else {
+                reader.analog_time_offset(i).unwrap_or_default()
+            }
+        })
+        .collect();
+
+    if let Some(dir) = args.output_dir.as_ref() {
+        fs::create_dir_all(dir)?;
+        for (i, mut trace) in reader.iter_analogs().enumerate() {
+            let path = dir
+                .join(channel_file_stem(i, &trace.name))
+                .with_extension("csv");
+            trace.time.iter_mut().for_each(|t| *t += offsets[i]);
+            let mut out = io::BufWriter::new(fs::File::create(&path)?);
+            trace.to_csv(&mut out, &DelimitedOptions::csv())?;
+            out.flush()?;
+            log::info!("Wrote {} to {}", trace.name, path.display());
+        }
+    } else {
+        let mut out = args.output.open()?;
+        writeln!(out, "channel,name,unit,offset,time,intensity")?;
+        for (i, trace) in reader.iter_analogs().enumerate() {
+            let name = csv_field(&trace.name);
+            let unit = csv_field(&trace.unit);
+            for (time, intensity) in trace.time.iter().zip(trace.intensity.iter()) {
+                writeln!(
+                    out,
+                    "{i},{name},{unit},{},{},{intensity}",
+                    offsets[i],
+                    time + offsets[i]
+                )?;
+            }
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// A reference ion that CCS inputs are matched against
+#[derive(Debug, Clone)]
+struct ReferenceIon {
+    name: String,
+    mz: f64,
+    charge: Option<i32>,
+}
+
+fn read_reference_ions(path: &Path) -> CliResult<Vec<ReferenceIon>> {
+    let mut reader = open_delimited(path)?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let mz_col =
+        column("mz").ok_or_else(|| format!("{} does not have an `mz` column", path.display()))?;
+    let name_col = column("name");
+    let charge_col = column("charge");
+
+    let mut references = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        let line = i + 2;
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).filter(|v| !v.is_empty());
+        let mz: f64 = field(Some(mz_col))
+            .ok_or_else(|| format!("Reference on line {line} has no m/z"))?
+            .parse()
+            .map_err(|e| format!("Failed to parse m/z on line {line}: {e}"))?;
+        let charge = field(charge_col)
+            .map(|v| v.parse())
+            .transpose()
+            .map_err(|e| format!("Failed to parse charge on line {line}: {e}"))?;
+        let name = field(name_col)
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| mz.to_string());
+        references.push(ReferenceIon { name, mz, charge });
+    }
+    Ok(references)
+}
+
+/// Find the reference closest to `mz` within `ppm`, returning it with the mass error in ppm
+fn match_reference(references: &[ReferenceIon], mz: f64, ppm: f64) -> Option<(&ReferenceIon, f64)> {
+    references
+        .iter()
+        .map(|r| (r, (mz - r.mz) / r.mz * 1e6))
+        .filter(|(_, error)| error.abs() <= ppm)
+        .min_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+}
+
+fn ccs(args: CcsArgs) -> CliResult<()> {
+    let reader = open_reader(&args.path)?;
+    let references = args
+        .references
+        .as_deref()
+        .map(read_reference_ions)
+        .transpose()?;
+    let (input_column, output_column) = match args.direction {
+        CcsDirection::DriftToCcs => ("drift_time", "ccs"),
+        CcsDirection::CcsToDrift => ("ccs", "drift_time"),
+    };
+
+    let mut writer = csv::Writer::from_writer(args.output.open()?);
+    let mut header = vec!["source", "mz", "charge", input_column, output_column];
+    if references.is_some() {
+        header.extend(["reference", "reference_mz", "ppm_error"]);
+    }
+    writer.write_record(&header)?;
+
+    for input in args.inputs.iter() {
+        let mut reader_in = open_delimited(input)?;
+        let headers = reader_in.headers()?.clone();
+        let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+        let mz_col = column("mz")
+            .ok_or_else(|| format!("{} does not have an `mz` column", input.display()))?;
+        let value_col = column(input_column).ok_or_else(|| {
+            format!(
+                "{} does not have a `{input_column}` column",
+                input.display()
+            )
+        })?;
+        let charge_col = column("charge");
+        let source = input.display().to_string();
+
+        for (i, record) in reader_in.records().enumerate() {
+            let record = record?;
+            let line = i + 2;
+            let parse = |col: usize, what: &str| -> CliResult<f64> {
+                let value = record.get(col).unwrap_or_default();
+                value.parse().map_err(|e| {
+                    format!(
+                        "Failed to parse {what} {value:?} on line {line} of {}: {e}",
+                        input.display()
+                    )
+                    .into()
+                })
+            };
+            let mz = parse(mz_col, "m/z")?;
+            let value = parse(value_col, input_column)?;
+
+            let matched = references
+                .as_deref()
+                .and_then(|refs| match_reference(refs, mz, args.ppm));
+            let charge = match charge_col
+                .and_then(|c| record.get(c))
+                .filter(|v| !v.is_empty())
+            {
+                Some(v) => v.parse().map_err(|e| {
+                    format!(
+                        "Failed to parse charge on line {line} of {}: {e}",
+                        input.display()
+                    )
+                })?,
+                None => matched.and_then(|(r, _)| r.charge).unwrap_or(args.charge),
+            };
+
+            let converted = match args.direction {
+                CcsDirection::DriftToCcs => {
+                    reader.collisional_cross_section(value as f32, mz as f32, charge)?
+                }
+                CcsDirection::CcsToDrift => {
+                    reader.drift_time_for_ccs(value as f32, mz as f32, charge)?
+                }
+            };
+
+            let mut row = vec![
+                source.clone(),
+                mz.to_string(),
+                charge.to_string(),
+                value.to_string(),
+                converted.to_string(),
+            ];
+            if references.is_some() {
+                match matched {
+                    Some((reference, error)) => row.extend([
+                        reference.name.clone(),
+                        reference.mz.to_string(),
+                        format!("{error:.3}"),
+                    ]),
+                    None => row.extend([String::new(), String::new(), String::new()]),
+                }
+            }
+            writer.write_record(&row)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Parse a positive, finite number of seconds
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value.parse().map_err(|e| format!("{e}"))?;
+    if !seconds.is_finite() || seconds <= 0.0 {
+        return Err(format!("{value} is not a positive number of seconds"));
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|e| format!("{e}"))
+}
+
+fn watch(args: WatchArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    let mut out = args.output.open()?;
+    if args.spectra {
+        writeln!(out, "index\tfunction\ttime\tmz\tintensity")?;
+    } else {
+        writeln!(out, "index\tfunction\ttime\ttic")?;
+    }
+
+    let mut next_cycle = 0;
+    let mut idle = 0;
+    loop {
+        let n_cycles = reader.cycle_index().len();
+        for cycle in next_cycle..n_cycles {
+            let Some(spectra) = reader.spectrum_range_of_cycle(cycle) else {
+                continue;
+            };
+            for i in spectra {
+                // Lock mass spectra are skipped by the reader
+                let spectrum = match reader.try_get_spectrum(i) {
+                    Ok(spectrum) => spectrum,
+                    Err(e) => {
+                        log::debug!("Skipping spectrum {i}: {e}");
+                        continue;
+                    }
+                };
+                let function = spectrum.identifier.function;
+                if args.spectra {
+                    for (mz, intensity) in spectrum
+                        .mz_array
+                        .iter()
+                        .zip(spectrum.intensity_array.iter())
+                    {
+                        writeln!(out, "{i}\t{function}\t{}\t{mz}\t{intensity}", spectrum.time)?;
+                    }
+                } else {
+                    let tic: f32 = spectrum.intensity_array.iter().sum();
+                    writeln!(out, "{i}\t{function}\t{}\t{tic}", spectrum.time)?;
+                }
+            }
+        }
+        out.flush()?;
+        next_cycle = n_cycles;
+
+        std::thread::sleep(args.interval);
+        let n_new = reader.refresh()?;
+        log::debug!("Found {n_new} new cycles");
+        if n_new == 0 {
+            idle += 1;
+            if args.idle_limit.is_some_and(|limit| idle >= limit) {
+                break;
+            }
+        } else {
+            idle = 0;
+        }
+    }
+    Ok(())
+}
+
+fn lockmass(args: LockmassArgs) -> CliResult<()> {
+    let mut reader = open_reader(&args.path)?;
+    if args.remove {
+        reader.remove_lockmass_correction()?;
+        println!("Removed lock mass correction");
+    }
+    let settings = match (args.apply, args.compound) {
+        (Some(mass), _) => Some(LockMassSettings::new(mass, args.tolerance)),
+        (None, Some(compound)) => {
+            let function = reader.get_lock_mass_function().ok_or_else(|| {
+                format!("Cannot pick a {compound:?} reference ion without a lock mass function")
+            })?;
+            let polarity = reader.ion_mode(function)?.polarity();
+            let settings =
+                LockMassSettings::from_compound(compound, polarity).ok_or_else(|| {
+                    format!("{compound:?} has no reference ion in {polarity:?} polarity")
+                })?;
+            Some(LockMassSettings {
+                tolerance: args.tolerance,
+                ..settings
+            })
+        }
+        (None, None) => None,
+    };
+    if let Some(settings) = settings {
+        let mass = settings.mass;
+        reader.set_lock_mass_settings(&settings)?;
+        if reader.is_lock_mass_corrected() {
+            println!("Applied lock mass correction at m/z {mass}");
+        } else {
+            return Err(format!("Could not apply a lock mass correction at m/z {mass}").into());
+        }
+    }
+
+    match reader.get_lock_mass_function() {
+        Some(f) => println!("Lock mass function: {f}"),
+        None => println!("Lock mass function: none"),
+    }
+    println!("Corrected: {}", reader.is_lock_mass_corrected());
+    println!("Can correct: {}", reader.can_lock_mass_correct());
+    if !args.gain_at.is_empty() {
+        println!("time\tgain");
+        for time in args.gain_at.iter() {
+            println!("{time}\t{}", reader.lock_mass_gain(*time)?);
+        }
+    }
+    Ok(())
+}
+
+/// Expand the batch inputs into RAW paths, treating any input with glob syntax as a pattern
+fn expand_batch_inputs(inputs: &[String]) -> CliResult<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        if input.contains(['*', '?', '[']) {
+            let n = paths.len();
+            for entry in glob::glob(input)? {
+                paths.push(entry?);
+            }
+            if paths.len() == n {
+                log::warn!("{input} did not match any paths");
+            }
+        } else {
+            paths.push(PathBuf::from(input));
+        }
+    }
+    Ok(paths)
+}
+
+/// Build the argument list for running a batch subcommand on one run.
+///
+/// When there are several runs and no argument uses `{stem}`, output files get the run name
+/// inserted before their extension and output directories get a subdirectory per run, so runs
+/// do not overwrite one another.
+fn batch_run_arguments(command: &[String], path: &Path, n_runs: usize) -> Vec<String> {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let uses_stem = command.iter().any(|a| a.contains("{stem}"));
+
+    let mut args = vec![env!("CARGO_PKG_NAME").to_string(), command[0].clone()];
+    args.push(path.display().to_string());
+    let mut rest = command[1..].iter();
+    while let Some(arg) = rest.next() {
+        if uses_stem || n_runs < 2 {
+            args.push(arg.replace("{stem}", &stem));
+            continue;
+        }
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        // `-d` is only the short form of `--output-dir` for `export-analog`
+        let is_output_dir =
+            flag == "--output-dir" || (flag == "-d" && command[0] == "export-analog");
+        if is_output_dir || flag == "-o" || flag == "--output" {
+            let value = match value {
+                Some(value) => value,
+                None => match rest.next() {
+                    Some(value) => value.clone(),
+                    None => {
+                        args.push(arg.clone());
+                        continue;
+                    }
+                },
+            };
+            let value = PathBuf::from(value);
+            let value = if is_output_dir {
+                value.join(&stem)
+            } else {
+                let mut name = value
+                    .file_stem()
+                    .map(|s| s.to_os_string())
+                    .unwrap_or_default();
+                name.push(format!(".{stem}"));
+                if let Some(ext) = value.extension() {
+                    name.push(".");
+                    name.push(ext);
+                }
+                value.with_file_name(name)
+            };
+            args.push(flag.to_string());
+            args.push(value.display().to_string());
+        } else {
+            args.push(arg.clone());
+        }
+    }
+    args
+}
+
+fn batch(args: BatchArgs) -> CliResult<()> {
+    if args.command[0] == "batch" {
+        return Err("batch cannot run itself".into());
+    }
+    let paths = expand_batch_inputs(&args.inputs)?;
+    if paths.is_empty() {
+        return Err("No RAW directories matched the batch inputs".into());
+    }
+
+    let mut summary = Vec::with_capacity(paths.len());
+    let mut first_error = None;
+    for path in paths.iter() {
+        let run_args = batch_run_arguments(&args.command, path, paths.len());
+        log::info!("Processing {}", path.display());
+        let start = std::time::Instant::now();
+        let result = Cli::try_parse_from(&run_args)
+            .map_err(|e| -> Box<dyn Error> { e.into() })
+            .and_then(|cli| run(cli.command));
+        let elapsed = start.elapsed().as_secs_f64();
+        match result {
+            Ok(()) => summary.push((path, "ok", elapsed, String::new())),
+            Err(e) => {
+                let message = e.to_string().lines().next().unwrap_or_default().to_string();
+                summary.push((path, "failed", elapsed, message));
+                if first_error.is_none() {
+                    first_error = Some(e);
+                }
+                if !args.continue_on_error {
+                    break;
+                }
+            }
+        }
+    }
+
+    eprintln!("run\tstatus\tseconds\tmessage");
+    for (path, status, elapsed, message) in summary.iter() {
+        eprintln!("{}\t{status}\t{elapsed:.2}\t{message}", path.display());
+    }
+    let n_failed = summary.iter().filter(|(_, s, _, _)| *s == "failed").count();
+    match first_error {
+        Some(e) if !args.continue_on_error => Err(e),
+        Some(_) => Err(format!("{n_failed} of {} runs failed", paths.len()).into()),
+        None => Ok(()),
+    }
+}
+
+fn run(command: Command) -> CliResult<()> {
+    match command {
+        Command::Info(args) => info(args),
+        Command::Meta(args) => meta(args),
+        Command::Qc(args) => qc(args),
+        Command::Validate(args) => validate(args),
+        Command::Functions(args) => functions(args),
+        Command::ScanItems(args) => scan_items(args),
+        Command::Header(args) => header(args),
+        Command::Tic(args) => chromatogram(args, false),
+        Command::Bpi(args) => chromatogram(args, true),
+        Command::Xic(args) => xic(args),
+        Command::Spectrum(args) => spectrum(args),
+        Command::Cycle(args) => cycle(args),
+        Command::Mobilogram(args) => mobilogram(args),
+        Command::Analog(args) => analog(args),
+        Command::ExportAnalog(args) => export_analog(args),
+        Command::Ccs(args) => ccs(args),
+        Command::Watch(args) => watch(args),
+        Command::Lockmass(args) => lockmass(args),
+        Command::Batch(args) => batch(args),
+    }
+}
+
+fn main() -> CliResult<()> {
+    pretty_env_logger::init_timed();
+    let cli = Cli::parse();
+    run(cli.command)
+}
@@ -0,0 +1,150 @@
+//! Describe a RAW directory from its text files and file listing alone, without the SDK.
+//!
+//! [`RawMetadata`] reads the `_HEADER.TXT` and `_extern.inf` files and lists the data files of
+//! each function. Function types and scan counts are stored in binary files whose layout is
+//! not published, so they are only available through [`MassLynxReader`](crate::reader::MassLynxReader).
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::base::decode_text;
+use crate::constants::MassLynxHeaderItem;
+use crate::reader::{header_file_key_matches, read_header_file, RawPaths};
+
+/// The data files of one function found in the RAW directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionFiles {
+    /// The function number, starting from 0
+    pub function: usize,
+    pub data_path: PathBuf,
+    /// The size of the data file in bytes
+    pub data_size: u64,
+    pub has_index: bool,
+    /// Whether the function has a drift time (`.cdt`) file
+    pub has_cdt: bool,
+    pub chromatogram_path: Option<PathBuf>,
+}
+
+/// A `name`, `value` line of the `_extern.inf` file, under the heading `section`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstrumentParameter {
+    pub section: String,
+    pub name: String,
+    pub value: String,
+}
+
+/// What a RAW directory describes about itself in plain files
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawMetadata {
+    pub path: PathBuf,
+    /// The `$$ key: value` lines of `_HEADER.TXT`
+    pub headers: HashMap<String, String>,
+    /// The instrument settings from `_extern.inf`, in file order
+    pub instrument_parameters: Vec<InstrumentParameter>,
+    /// The functions with a data file, ordered by function number
+    pub functions: Vec<FunctionFiles>,
+}
+
+impl RawMetadata {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if !path.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not a directory", path.display()),
+            ));
+        }
+        let raw_paths = RawPaths::from_path(path.clone())?;
+        let headers = read_header_file(&path)?;
+        let instrument_parameters = match find_file(&path, "_extern.inf")? {
+            Some(extern_path) => parse_extern_inf(&fs::read(extern_path)?),
+            None => Vec::new(),
+        };
+
+        let mut functions: Vec<FunctionFiles> = raw_paths
+            .function_paths
+            .iter()
+            .map(|(function, data_path)| FunctionFiles {
+                function: *function,
+                data_path: data_path.clone(),
+                data_size: fs::metadata(data_path).map(|m| m.len()).unwrap_or_default(),
+                has_index: data_path.with_extension("idx").exists(),
+                has_cdt: raw_paths.function_has_cdt(*function),
+                chromatogram_path: raw_paths.chromatogram_paths.get(function).cloned(),
+            })
+            .collect();
+        functions.sort_by_key(|f| f.function);
+
+        Ok(Self {
+            path,
+            headers,
+            instrument_parameters,
+            functions,
+        })
+    }
+
+    /// The header file values that correspond to SDK header items, in item order
+    pub fn header_items(&self) -> Vec<(MassLynxHeaderItem, String)> {
+        MassLynxHeaderItem::iter()
+            .filter_map(|item| {
+                self.headers
+                    .iter()
+                    .find(|(k, _)| header_file_key_matches(item, k))
+                    .map(|(_, v)| (item, v.clone()))
+            })
+            .filter(|(_, v)| !v.is_empty())
+            .collect()
+    }
+
+    /// The instrument parameters under headings that mention `function`, counting from 0
+    pub fn function_parameters(&self, function: usize) -> Vec<&InstrumentParameter> {
+        let needle = format!("function {}", function + 1);
+        self.instrument_parameters
+            .iter()
+            .filter(|p| {
+                let section = p.section.to_lowercase();
+                section.match_indices(&needle).any(|(i, _)| {
+                    !section[i + needle.len()..].starts_with(|c: char| c.is_ascii_digit())
+                })
+            })
+            .collect()
+    }
+}
+
+/// Find `name` in `root`, ignoring case
+fn find_file(root: &Path, name: &str) -> io::Result<Option<PathBuf>> {
+    Ok(fs::read_dir(root)?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|p| p.file_name().is_some_and(|n| n.eq_ignore_ascii_case(name))))
+}
+
+/// Split `_extern.inf` into tab separated name and value lines under the untabbed heading
+/// lines before them.
+fn parse_extern_inf(bytes: &[u8]) -> Vec<InstrumentParameter> {
+    let text = decode_text(bytes);
+    let mut section = String::new();
+    let mut parameters = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.split_once('\t') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                parameters.push(InstrumentParameter {
+                    section: section.clone(),
+                    name: name.trim().to_string(),
+                    value: value.trim().to_string(),
+                });
+            }
+            _ => section = line.trim().trim_end_matches(':').to_string(),
+        }
+    }
+    parameters
+}
@@ -0,0 +1,781 @@
+//! Utilities for working with ion mobility cycles.
+//!
+//! [`align_mse_cycles`] groups the fragments of an HDMSe high energy cycle with the precursors
+//! of the low energy cycle acquired alongside it by their shared drift time.
+//! [`mse_cycle_pairs`] finds those low and high energy cycle pairs in a cycle index.
+//!
+//! [`Cycle::to_matrix`] and [`Cycle::to_sparse_matrix`] rasterize a cycle's drift scans onto a
+//! common m/z grid described by a [`BinSpec`].
+//!
+//! [`MassLynxReader::sonar_map`] sums the cycles of a SONAR function into a precursor by
+//! fragment m/z map, using [`SonarQuadMapping`] to place each bin's quadrupole window.
+
+use crate::base::UNSUPPORTED_ERROR_CODE;
+use crate::constants::MassLynxScanItem;
+use crate::reader::{function_out_of_range, Cycle, CycleIndexEntry, DriftScan, MassLynxReader};
+use crate::{MassLynxError, MassLynxErrorKind, MassLynxResult};
+
+/// An ion summarized over the drift scans of a cycle
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriftPeak {
+    /// The intensity-weighted mean m/z
+    pub mz: f64,
+    /// The summed intensity over every drift scan
+    pub intensity: f32,
+    /// The intensity-weighted mean drift time, in milliseconds
+    pub drift_time: f64,
+}
+
+/// A precursor and the fragments that drift with it
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriftAlignment {
+    pub precursor: DriftPeak,
+    pub fragments: Vec<DriftPeak>,
+}
+
+/// Controls how peaks are summarized and matched by [`align_mse_cycles`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftAlignmentOptions {
+    /// The largest drift time difference between a precursor and its fragments, in milliseconds
+    pub drift_tolerance: f64,
+    /// The m/z tolerance, in parts per million, for merging a peak across drift scans
+    pub mz_tolerance_ppm: f64,
+    /// Ignore low energy peaks below this intensity
+    pub min_precursor_intensity: f32,
+    /// Ignore high energy peaks below this intensity
+    pub min_fragment_intensity: f32,
+    /// Only group fragments with a lower m/z than their precursor. This suits singly charged
+    /// precursors.
+    pub fragments_below_precursor: bool,
+}
+
+impl Default for DriftAlignmentOptions {
+    fn default() -> Self {
+        Self {
+            drift_tolerance: 0.2,
+            mz_tolerance_ppm: 20.0,
+            min_precursor_intensity: 0.0,
+            min_fragment_intensity: 0.0,
+            fragments_below_precursor: false,
+        }
+    }
+}
+
+/// Merge the peaks of a cycle's drift scans that share an m/z into one [`DriftPeak`] each,
+/// sorted by m/z
+pub fn summarize_drift_peaks(
+    cycle: &Cycle,
+    mz_tolerance_ppm: f64,
+    min_intensity: f32,
+) -> Vec<DriftPeak> {
+    let mut peaks: Vec<(f64, f32, f64)> = cycle
+        .signal
+        .iter()
+        .flat_map(|scan| {
+            scan.mz_array
+                .iter()
+                .zip(scan.intensity_array.iter())
+                .filter(|(_, intensity)| **intensity > min_intensity)
+                .map(|(mz, intensity)| (*mz as f64, *intensity, scan.drift_time))
+        })
+        .collect();
+    peaks.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut merged = Vec::new();
+    let mut start = 0;
+    while start < peaks.len() {
+        let anchor = peaks[start].0;
+        let limit = anchor + anchor * mz_tolerance_ppm / 1e6;
+        let end = start
+            + peaks[start..]
+                .iter()
+                .take_while(|(mz, _, _)| *mz <= limit)
+                .count();
+        let group = &peaks[start..end];
+        let total: f64 = group.iter().map(|(_, i, _)| *i as f64).sum();
+        if total > 0.0 {
+            merged.push(DriftPeak {
+                mz: group.iter().map(|(mz, i, _)| mz * *i as f64).sum::<f64>() / total,
+                intensity: total as f32,
+                drift_time: group.iter().map(|(_, i, dt)| dt * *i as f64).sum::<f64>() / total,
+            });
+        }
+        start = end;
+    }
+    merged
+}
+
+/// Group the fragments of the high energy cycle `high` with the precursors of the low energy
+/// cycle `low` whose drift times are within [`DriftAlignmentOptions::drift_tolerance`].
+///
+/// Both cycles should be from the same point in an HDMSe run, see [`mse_cycle_pairs`]. A
+/// fragment may be grouped with more than one precursor. The groups are returned in precursor
+/// m/z order, and precursors without any fragments are left out.
+pub fn align_mse_cycles(
+    low: &Cycle,
+    high: &Cycle,
+    options: &DriftAlignmentOptions,
+) -> Vec<DriftAlignment> {
+    let precursors = summarize_drift_peaks(
+        low,
+        options.mz_tolerance_ppm,
+        options.min_precursor_intensity,
+    );
+    let mut fragments = summarize_drift_peaks(
+        high,
+        options.mz_tolerance_ppm,
+        options.min_fragment_intensity,
+    );
+    fragments.sort_by(|a, b| a.drift_time.total_cmp(&b.drift_time));
+
+    precursors
+        .into_iter()
+        .filter_map(|precursor| {
+            let lo = precursor.drift_time - options.drift_tolerance;
+            let hi = precursor.drift_time + options.drift_tolerance;
+            let first = fragments.partition_point(|f| f.drift_time < lo);
+            let mut matched: Vec<DriftPeak> = fragments[first..]
+                .iter()
+                .take_while(|f| f.drift_time <= hi)
+                .filter(|f| !options.fragments_below_precursor || f.mz < precursor.mz)
+                .copied()
+                .collect();
+            if matched.is_empty() {
+                return None;
+            }
+            matched.sort_by(|a, b| a.mz.total_cmp(&b.mz));
+            Some(DriftAlignment {
+                precursor,
+                fragments: matched,
+            })
+        })
+        .collect()
+}
+
+/// Pair each cycle of `low_function` with the cycle of `high_function` acquired closest to it
+/// in time, no more than `max_time_difference` minutes apart.
+///
+/// Returns the pairs of cycle indices, in the order of `cycles`.
+pub fn mse_cycle_pairs(
+    cycles: &[CycleIndexEntry],
+    low_function: usize,
+    high_function: usize,
+    max_time_difference: f64,
+) -> Vec<(usize, usize)> {
+    let mut high: Vec<&CycleIndexEntry> = cycles
+        .iter()
+        .filter(|e| e.function == high_function)
+        .collect();
+    high.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+    cycles
+        .iter()
+        .filter(|e| e.function == low_function)
+        .filter_map(|low| {
+            let i = high.partition_point(|h| h.time < low.time);
+            let before = i.checked_sub(1).and_then(|j| high.get(j));
+            let after = high.get(i);
+            let closest = match (before, after) {
+                (Some(a), Some(b)) => {
+                    if low.time - a.time <= b.time - low.time {
+                        a
+                    } else {
+                        b
+                    }
+                }
+                (Some(a), None) => a,
+                (None, Some(b)) => b,
+                (None, None) => return None,
+            };
+            ((closest.time - low.time).abs() <= max_time_difference)
+                .then_some((low.index, closest.index))
+        })
+        .collect()
+}
+
+/// An evenly spaced m/z grid
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BinSpec {
+    /// The lower edge of the first bin
+    pub start: f64,
+    /// The upper edge of the last bin
+    pub end: f64,
+    /// The width of each bin
+    pub width: f64,
+    /// Drop peaks whose intensity is not above this before binning
+    pub min_intensity: Option<f32>,
+}
+
+impl BinSpec {
+    pub fn new(start: f64, end: f64, width: f64) -> Self {
+        Self {
+            start,
+            end,
+            width,
+            min_intensity: None,
+        }
+    }
+
+    pub fn with_min_intensity(mut self, min_intensity: f32) -> Self {
+        self.min_intensity = Some(min_intensity);
+        self
+    }
+
+    /// The number of bins, counting a partial bin at the end
+    pub fn len(&self) -> usize {
+        let finite = self.start.is_finite() && self.end.is_finite() && self.width.is_finite();
+        if !finite || self.width <= 0.0 || self.end <= self.start {
+            0
+        } else {
+            ((self.end - self.start) / self.width).ceil() as usize
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The bin holding `mz`, or `None` if it is outside the grid or not a finite number
+    pub fn bin_of(&self, mz: f64) -> Option<usize> {
+        if !mz.is_finite() || mz < self.start || mz >= self.end || self.is_empty() {
+            return None;
+        }
+        Some((((mz - self.start) / self.width) as usize).min(self.len() - 1))
+    }
+
+    /// The m/z at the center of each bin
+    pub fn centers(&self) -> Vec<f64> {
+        (0..self.len())
+            .map(|i| self.start + self.width * (i as f64 + 0.5))
+            .collect()
+    }
+
+    fn binned_peaks<'a>(&'a self, scan: &'a DriftScan) -> impl Iterator<Item = (usize, f32)> + 'a {
+        scan.mz_array
+            .iter()
+            .zip(scan.intensity_array.iter())
+            .filter(|(_, intensity)| self.min_intensity.is_none_or(|min| **intensity > min))
+            .filter_map(|(mz, intensity)| Some((self.bin_of(*mz as f64)?, *intensity)))
+    }
+}
+
+/// The intensities of a cycle's drift scans on a common m/z grid, with one row per drift scan
+/// and one column per m/z bin, stored in row-major order
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriftMatrix {
+    pub mz_bins: BinSpec,
+    /// The drift time of each row, in milliseconds
+    pub drift_times: Vec<f64>,
+    pub intensities: Vec<f32>,
+}
+
+impl DriftMatrix {
+    /// The number of rows and columns
+    pub fn shape(&self) -> (usize, usize) {
+        (self.drift_times.len(), self.mz_bins.len())
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<f32> {
+        let (n_rows, n_columns) = self.shape();
+        if row >= n_rows || column >= n_columns {
+            return None;
+        }
+        self.intensities.get(row * n_columns + column).copied()
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[f32]> {
+        let n_columns = self.mz_bins.len();
+        self.intensities.get(row * n_columns..(row + 1) * n_columns)
+    }
+}
+
+/// The non-zero cells of a [`DriftMatrix`] as coordinate lists, ordered by row then column
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SparseDriftMatrix {
+    pub mz_bins: BinSpec,
+    /// The drift time of each row, in milliseconds
+    pub drift_times: Vec<f64>,
+    pub rows: Vec<usize>,
+    pub columns: Vec<usize>,
+    pub values: Vec<f32>,
+}
+
+impl SparseDriftMatrix {
+    /// The number of rows and columns
+    pub fn shape(&self) -> (usize, usize) {
+        (self.drift_times.len(), self.mz_bins.len())
+    }
+
+    /// The number of stored cells
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn to_dense(&self) -> DriftMatrix {
+        let (n_rows, n_columns) = self.shape();
+        let mut intensities = vec![0.0; n_rows * n_columns];
+        for ((row, column), value) in self.rows.iter().zip(&self.columns).zip(&self.values) {
+            intensities[row * n_columns + column] += value;
+        }
+        DriftMatrix {
+            mz_bins: self.mz_bins,
+            drift_times: self.drift_times.clone(),
+            intensities,
+        }
+    }
+}
+
+impl Cycle {
+    /// Sum the intensities of each drift scan into the bins of `mz_bins`. Peaks outside the
+    /// grid are dropped.
+    pub fn to_matrix(&self, mz_bins: &BinSpec) -> DriftMatrix {
+        let n_columns = mz_bins.len();
+        let mut intensities = vec![0.0; self.signal.len() * n_columns];
+        for (row, scan) in self.signal.iter().enumerate() {
+            for (column, intensity) in mz_bins.binned_peaks(scan) {
+                intensities[row * n_columns + column] += intensity;
+            }
+        }
+        DriftMatrix {
+            mz_bins: *mz_bins,
+            drift_times: self.signal.iter().map(|s| s.drift_time).collect(),
+            intensities,
+        }
+    }
+
+    /// As [`Cycle::to_matrix`], storing only the bins that received a peak
+    pub fn to_sparse_matrix(&self, mz_bins: &BinSpec) -> SparseDriftMatrix {
+        let mut rows = Vec::new();
+        let mut columns = Vec::new();
+        let mut values = Vec::new();
+        let mut cells: Vec<(usize, f32)> = Vec::new();
+        for (row, scan) in self.signal.iter().enumerate() {
+            cells.clear();
+            cells.extend(mz_bins.binned_peaks(scan));
+            cells.sort_by_key(|(column, _)| *column);
+            for (column, intensity) in cells.iter().copied() {
+                if columns.last() == Some(&column) && rows.last() == Some(&row) {
+                    *values.last_mut().unwrap() += intensity;
+                } else {
+                    rows.push(row);
+                    columns.push(column);
+                    values.push(intensity);
+                }
+            }
+        }
+        SparseDriftMatrix {
+            mz_bins: *mz_bins,
+            drift_times: self.signal.iter().map(|s| s.drift_time).collect(),
+            rows,
+            columns,
+            values,
+        }
+    }
+}
+
+/// How the bins of a SONAR cycle map onto quadrupole isolation windows, assuming the
+/// quadrupole sweeps linearly from [`SonarQuadMapping::start_mass`] to
+/// [`SonarQuadMapping::stop_mass`] over the bins
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SonarQuadMapping {
+    pub start_mass: f64,
+    pub stop_mass: f64,
+    /// The width of the quadrupole window, if the scan recorded it
+    pub peak_width: Option<f64>,
+    pub n_bins: usize,
+}
+
+impl SonarQuadMapping {
+    /// Read the quadrupole sweep from `cycle`'s scan items, or `None` if the cycle has no
+    /// drift scans or lacks the start and stop masses
+    pub fn from_cycle(cycle: &Cycle) -> Option<Self> {
+        let item = |key: MassLynxScanItem| cycle.scan_item_number(key);
+        let n_bins = cycle.signal.len();
+        if n_bins == 0 {
+            return None;
+        }
+        Some(Self {
+            start_mass: item(MassLynxScanItem::QUAD_START_MASS)?,
+            stop_mass: item(MassLynxScanItem::QUAD_STOP_MASS)?,
+            peak_width: item(MassLynxScanItem::QUAD_PEAK_WIDTH).filter(|w| *w > 0.0),
+            n_bins,
+        })
+    }
+
+    /// The m/z at the center of the quadrupole window of `bin`
+    pub fn precursor_mz(&self, bin: usize) -> f64 {
+        if self.n_bins < 2 {
+            return self.start_mass;
+        }
+        let fraction = bin as f64 / (self.n_bins - 1) as f64;
+        self.start_mass + (self.stop_mass - self.start_mass) * fraction
+    }
+
+    /// The m/z range transmitted by the quadrupole in `bin`, or just its center if the window
+    /// width is unknown
+    pub fn window(&self, bin: usize) -> (f64, f64) {
+        let center = self.precursor_mz(bin);
+        let half_width = self.peak_width.unwrap_or_default() / 2.0;
+        (center - half_width, center + half_width)
+    }
+}
+
+/// Fragment intensities summed over the cycles of a SONAR function, with one row per
+/// quadrupole bin and one column per fragment m/z bin, stored in row-major order
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SonarMap {
+    pub quad: SonarQuadMapping,
+    pub mz_bins: BinSpec,
+    pub intensities: Vec<f32>,
+    /// The number of cycles summed
+    pub n_cycles: usize,
+}
+
+impl SonarMap {
+    /// The number of rows and columns
+    pub fn shape(&self) -> (usize, usize) {
+        (self.quad.n_bins, self.mz_bins.len())
+    }
+
+    /// The precursor m/z of each row
+    pub fn precursor_mzs(&self) -> Vec<f64> {
+        (0..self.quad.n_bins)
+            .map(|bin| self.quad.precursor_mz(bin))
+            .collect()
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<f32> {
+        let (n_rows, n_columns) = self.shape();
+        if row >= n_rows || column >= n_columns {
+            return None;
+        }
+        self.intensities.get(row * n_columns + column).copied()
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[f32]> {
+        let n_columns = self.mz_bins.len();
+        self.intensities.get(row * n_columns..(row + 1) * n_columns)
+    }
+
+    /// The summed intensity of each fragment m/z bin over the quadrupole bins whose window
+    /// contains `precursor_mz`
+    pub fn fragments_of(&self, precursor_mz: f64) -> Vec<f32> {
+        let mut fragments = vec![0.0; self.mz_bins.len()];
+        for bin in 0..self.quad.n_bins {
+            let (lo, hi) = self.quad.window(bin);
+            if lo <= precursor_mz && precursor_mz <= hi {
+                if let Some(row) = self.row(bin) {
+                    fragments.iter_mut().zip(row).for_each(|(f, i)| *f += i);
+                }
+            }
+        }
+        fragments
+    }
+}
+
+impl MassLynxReader {
+    /// Sum the cycles of the SONAR function `which_function` acquired between `start_time`
+    /// and `end_time` (in minutes), inclusive, into a precursor by fragment m/z map on the
+    /// `mz_bins` grid.
+    ///
+    /// Cycles whose number of bins differs from the first cycle's are skipped. Returns
+    /// `Ok(None)` if the function has no cycles in that interval, or if the first lacks the
+    /// quadrupole scan items.
+    pub fn sonar_map(
+        &mut self,
+        which_function: usize,
+        start_time: f64,
+        end_time: f64,
+        mz_bins: &BinSpec,
+    ) -> MassLynxResult<Option<SonarMap>> {
+        let function = self
+            .functions()
+            .get(which_function)
+            .ok_or_else(|| function_out_of_range(which_function, self.functions().len()))?;
+        if !function.is_sonar() || !function.has_drift_time() {
+            return Err(MassLynxError::new(
+                MassLynxErrorKind::Unsupported,
+                UNSUPPORTED_ERROR_CODE,
+                format!("Function {which_function} was not acquired with SONAR"),
+            ));
+        }
+
+        let indices: Vec<usize> = self
+            .cycle_index()
+            .iter()
+            .filter(|e| e.function == which_function && start_time <= e.time && e.time <= end_time)
+            .map(|e| e.index)
+            .collect();
+
+        let load_signal = self.get_signal_loading();
+        self.set_signal_loading(true);
+        let mut map: Option<SonarMap> = None;
+        let mut result = Ok(());
+        for index in indices {
+            let cycle = match self.try_get_cycle(index) {
+                Ok(cycle) => cycle,
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            };
+            let matrix = cycle.to_matrix(mz_bins);
+            match map.as_mut() {
+                Some(map) if map.intensities.len() == matrix.intensities.len() => {
+                    map.intensities
+                        .iter_mut()
+                        .zip(matrix.intensities)
+                        .for_each(|(a, b)| *a += b);
+                    map.n_cycles += 1;
+                }
+                // A cycle with a different number of bins cannot share the quadrupole mapping
+                Some(_) => {}
+                None => match SonarQuadMapping::from_cycle(&cycle) {
+                    Some(quad) => {
+                        map = Some(SonarMap {
+                            quad,
+                            mz_bins: *mz_bins,
+                            intensities: matrix.intensities,
+                            n_cycles: 1,
+                        });
+                    }
+                    // Later cycles are not tried, so no cycle is left out of the sum unseen
+                    None => break,
+                },
+            }
+        }
+        self.set_signal_loading(load_signal);
+        result.map(|_| map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(drift_time: f64, peaks: &[(f32, f32)]) -> DriftScan {
+        DriftScan::new(
+            drift_time,
+            peaks.iter().map(|(mz, _)| *mz).collect(),
+            peaks.iter().map(|(_, intensity)| *intensity).collect(),
+        )
+    }
+
+    fn cycle(signal: Vec<DriftScan>) -> Cycle {
+        Cycle {
+            signal,
+            ..Default::default()
+        }
+    }
+
+    fn entry(function: usize, time: f64, index: usize) -> CycleIndexEntry {
+        CycleIndexEntry::new(function, index, time, 200, index)
+    }
+
+    #[test]
+    fn test_bin_spec_len() {
+        assert_eq!(BinSpec::new(0.0, 10.0, 1.0).len(), 10);
+        assert_eq!(BinSpec::new(0.0, 10.0, 3.0).len(), 4);
+        assert!(BinSpec::new(5.0, 5.0, 1.0).is_empty());
+        assert!(BinSpec::new(10.0, 0.0, 1.0).is_empty());
+        assert!(BinSpec::new(0.0, 10.0, 0.0).is_empty());
+        assert!(BinSpec::new(0.0, 10.0, -1.0).is_empty());
+        assert!(BinSpec::new(0.0, 10.0, f64::NAN).is_empty());
+        assert!(BinSpec::new(0.0, f64::INFINITY, 1.0).is_empty());
+        assert!(BinSpec::new(f64::NAN, 10.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_bin_spec_bin_of() {
+        let bins = BinSpec::new(0.0, 10.0, 3.0);
+        assert_eq!(bins.bin_of(0.0), Some(0));
+        assert_eq!(bins.bin_of(2.999), Some(0));
+        assert_eq!(bins.bin_of(3.0), Some(1));
+        assert_eq!(bins.bin_of(9.999), Some(3));
+        assert_eq!(bins.bin_of(10.0), None);
+        assert_eq!(bins.bin_of(-0.001), None);
+        assert_eq!(bins.bin_of(f64::NAN), None);
+        assert_eq!(bins.bin_of(f64::INFINITY), None);
+        assert_eq!(bins.bin_of(f64::NEG_INFINITY), None);
+
+        assert_eq!(BinSpec::new(0.0, 10.0, 0.0).bin_of(5.0), None);
+        assert_eq!(BinSpec::new(5.0, 5.0, 1.0).bin_of(5.0), None);
+    }
+
+    #[test]
+    fn test_bin_spec_centers() {
+        assert_eq!(BinSpec::new(0.0, 4.0, 2.0).centers(), vec![1.0, 3.0]);
+        assert_eq!(BinSpec::new(0.0, 5.0, 2.0).centers(), vec![1.0, 3.0, 5.0]);
+        assert!(BinSpec::new(0.0, 4.0, 0.0).centers().is_empty());
+    }
+
+    #[test]
+    fn test_to_matrix() {
+        let bins = BinSpec::new(100.0, 104.0, 1.0);
+        let cycle = cycle(vec![
+            scan(1.5, &[(100.25, 1.0), (100.75, 2.0), (103.5, 4.0)]),
+            // Peaks outside the grid, at its upper edge and NaN m/z are dropped
+            scan(
+                2.5,
+                &[(99.0, 8.0), (104.0, 16.0), (f32::NAN, 32.0), (101.0, 64.0)],
+            ),
+        ]);
+        let matrix = cycle.to_matrix(&bins);
+        assert_eq!(matrix.shape(), (2, 4));
+        assert_eq!(matrix.drift_times, vec![1.5, 2.5]);
+        assert_eq!(matrix.row(0), Some(&[3.0, 0.0, 0.0, 4.0][..]));
+        assert_eq!(matrix.row(1), Some(&[0.0, 64.0, 0.0, 0.0][..]));
+        assert_eq!(matrix.row(2), None);
+        assert_eq!(matrix.get(1, 1), Some(64.0));
+        assert_eq!(matrix.get(0, 4), None);
+
+        let thresholded = cycle.to_matrix(&bins.with_min_intensity(2.0));
+        assert_eq!(thresholded.row(0), Some(&[0.0, 0.0, 0.0, 4.0][..]));
+    }
+
+    #[test]
+    fn test_to_matrix_edge_cases() {
+        let bins = BinSpec::new(100.0, 104.0, 1.0);
+        let empty = cycle(Vec::new()).to_matrix(&bins);
+        assert_eq!(empty.shape(), (0, 4));
+        assert!(empty.intensities.is_empty());
+
+        let no_bins =
+            cycle(vec![scan(1.0, &[(100.5, 1.0)])]).to_matrix(&BinSpec::new(0.0, 0.0, 1.0));
+        assert_eq!(no_bins.shape(), (1, 0));
+        assert!(no_bins.intensities.is_empty());
+
+        // A scan with more m/z values than intensities only bins the complete pairs
+        let ragged = DriftScan::new(1.0, vec![100.5, 101.5, 102.5], vec![1.0, 2.0]);
+        let matrix = cycle(vec![ragged]).to_matrix(&bins);
+        assert_eq!(matrix.row(0), Some(&[1.0, 2.0, 0.0, 0.0][..]));
+    }
+
+    #[test]
+    fn test_to_sparse_matrix() {
+        let bins = BinSpec::new(100.0, 104.0, 1.0);
+        let cycle = cycle(vec![
+            scan(1.5, &[(103.5, 4.0), (100.25, 1.0), (100.75, 2.0)]),
+            scan(2.0, &[]),
+            scan(2.5, &[(f32::NAN, 32.0), (101.0, 64.0)]),
+        ]);
+        let sparse = cycle.to_sparse_matrix(&bins);
+        assert_eq!(sparse.shape(), (3, 4));
+        assert_eq!(sparse.nnz(), 3);
+        assert_eq!(sparse.rows, vec![0, 0, 2]);
+        assert_eq!(sparse.columns, vec![0, 3, 1]);
+        assert_eq!(sparse.values, vec![3.0, 4.0, 64.0]);
+        assert_eq!(sparse.to_dense(), cycle.to_matrix(&bins));
+    }
+
+    #[test]
+    fn test_summarize_drift_peaks() {
+        let peaky = cycle(vec![
+            scan(1.0, &[(500.0, 1.0), (600.0, 0.5)]),
+            scan(3.0, &[(500.004, 3.0)]),
+        ]);
+        let peaks = summarize_drift_peaks(&peaky, 20.0, 0.0);
+        assert_eq!(peaks.len(), 2);
+        assert_eq!(peaks[0].intensity, 4.0);
+        assert!((peaks[0].mz - 500.003).abs() < 1e-3);
+        assert!((peaks[0].drift_time - 2.5).abs() < 1e-9);
+        assert_eq!(peaks[1].mz, 600.0);
+
+        let peaks = summarize_drift_peaks(&peaky, 1.0, 0.0);
+        assert_eq!(peaks.len(), 3);
+
+        let peaks = summarize_drift_peaks(&peaky, 20.0, 0.5);
+        assert_eq!(peaks.len(), 1);
+
+        assert!(summarize_drift_peaks(&cycle(Vec::new()), 20.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_align_mse_cycles() {
+        let low = cycle(vec![
+            scan(2.0, &[(500.0, 10.0)]),
+            scan(5.0, &[(800.0, 10.0)]),
+        ]);
+        let high = cycle(vec![
+            scan(2.1, &[(200.0, 1.0), (700.0, 1.0)]),
+            scan(3.0, &[(300.0, 1.0)]),
+        ]);
+        let options = DriftAlignmentOptions::default();
+        let groups = align_mse_cycles(&low, &high, &options);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].precursor.mz, 500.0);
+        let fragments: Vec<f64> = groups[0].fragments.iter().map(|f| f.mz).collect();
+        assert_eq!(fragments, vec![200.0, 700.0]);
+
+        let options = DriftAlignmentOptions {
+            fragments_below_precursor: true,
+            ..options
+        };
+        let groups = align_mse_cycles(&low, &high, &options);
+        assert_eq!(groups[0].fragments.len(), 1);
+        assert_eq!(groups[0].fragments[0].mz, 200.0);
+
+        let options = DriftAlignmentOptions {
+            drift_tolerance: 1.0,
+            ..options
+        };
+        let groups = align_mse_cycles(&low, &high, &options);
+        assert_eq!(groups[0].fragments.len(), 2);
+
+        assert!(align_mse_cycles(&low, &cycle(Vec::new()), &options).is_empty());
+    }
+
+    #[test]
+    fn test_mse_cycle_pairs() {
+        let cycles = vec![
+            entry(0, 1.0, 0),
+            entry(1, 1.02, 1),
+            entry(0, 1.05, 2),
+            entry(1, 1.07, 3),
+            entry(0, 2.0, 4),
+            entry(2, 1.01, 5),
+        ];
+        assert_eq!(mse_cycle_pairs(&cycles, 0, 1, 0.05), vec![(0, 1), (2, 3)]);
+        // A tie goes to the earlier high energy cycle
+        let tied = vec![entry(1, 0.9, 0), entry(0, 1.0, 1), entry(1, 1.1, 2)];
+        assert_eq!(mse_cycle_pairs(&tied, 0, 1, 1.0), vec![(1, 0)]);
+        assert!(mse_cycle_pairs(&cycles, 0, 3, 1.0).is_empty());
+        assert!(mse_cycle_pairs(&[], 0, 1, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_sonar_quad_mapping() {
+        let mut sonar = cycle(vec![scan(1.0, &[]), scan(2.0, &[]), scan(3.0, &[])]);
+        assert_eq!(SonarQuadMapping::from_cycle(&sonar), None);
+
+        sonar.items = vec![
+            (MassLynxScanItem::QUAD_START_MASS, "400".to_string()),
+            (MassLynxScanItem::QUAD_STOP_MASS, "600".to_string()),
+            (MassLynxScanItem::QUAD_PEAK_WIDTH, "0".to_string()),
+        ];
+        let quad = SonarQuadMapping::from_cycle(&sonar).unwrap();
+        assert_eq!(quad.n_bins, 3);
+        assert_eq!(quad.peak_width, None);
+        assert_eq!(quad.precursor_mz(0), 400.0);
+        assert_eq!(quad.precursor_mz(1), 500.0);
+        assert_eq!(quad.precursor_mz(2), 600.0);
+        assert_eq!(quad.window(1), (500.0, 500.0));
+
+        sonar.items[2].1 = "20".to_string();
+        let quad = SonarQuadMapping::from_cycle(&sonar).unwrap();
+        assert_eq!(quad.window(1), (490.0, 510.0));
+
+        let single = SonarQuadMapping { n_bins: 1, ..quad };
+        assert_eq!(single.precursor_mz(0), 400.0);
+
+        sonar.signal.clear();
+        assert_eq!(SonarQuadMapping::from_cycle(&sonar), None);
+    }
+}
@@ -0,0 +1,208 @@
+//! Read spectra and cycles on several threads at once, with the `rayon` feature.
+//!
+//! [`MassLynxReader::par_iter_spectra`] and [`MassLynxReader::par_iter_cycles`] read on a
+//! dedicated rayon thread pool where each worker thread opens its own copy of the reader once,
+//! see [`MassLynxReader::try_clone`]. The results are put back in index order before they reach
+//! the consumer, so they can replace [`MassLynxReader::iter_spectra`] and
+//! [`MassLynxReader::iter_cycles`] directly.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+
+use rayon::ThreadPoolBuilder;
+
+use crate::base::THREAD_POOL_ERROR_CODE;
+use crate::reader::{Cycle, MassLynxReader, Spectrum};
+use crate::{MassLynxError, MassLynxErrorKind, MassLynxResult};
+
+/// The number of items each worker thread may read ahead of the consumer
+const READ_AHEAD_PER_THREAD: usize = 2;
+
+#[derive(Debug, Default)]
+struct WindowState {
+    next: usize,
+    stopped: bool,
+}
+
+/// Hands out indices to the workers in order, holding back any index more than `size` past
+/// the next one the consumer is waiting for.
+///
+/// Because indices are claimed in order, the worker holding the consumer's next index is
+/// never held back, and at most `size` items are ever waiting to be consumed.
+#[derive(Debug)]
+struct ReadWindow {
+    len: usize,
+    size: usize,
+    claimed: AtomicUsize,
+    state: Mutex<WindowState>,
+    moved: Condvar,
+}
+
+impl ReadWindow {
+    fn new(len: usize, size: usize) -> Self {
+        Self {
+            len,
+            size,
+            claimed: AtomicUsize::new(0),
+            state: Mutex::default(),
+            moved: Condvar::new(),
+        }
+    }
+
+    /// Claim the next index to read, waiting until it is inside the window. Returns `None`
+    /// once every index is claimed or the consumer has stopped.
+    fn claim(&self) -> Option<usize> {
+        let index = self.claimed.fetch_add(1, Ordering::Relaxed);
+        if index >= self.len {
+            return None;
+        }
+        let state = self.state.lock().ok()?;
+        let state = self
+            .moved
+            .wait_while(state, |state| {
+                !state.stopped && index >= state.next + self.size
+            })
+            .ok()?;
+        (!state.stopped).then_some(index)
+    }
+
+    fn advance(&self, next: usize) {
+        if let Ok(mut state) = self.state.lock() {
+            state.next = next;
+        }
+        self.moved.notify_all();
+    }
+
+    fn stop(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.stopped = true;
+        }
+        self.moved.notify_all();
+    }
+}
+
+/// An iterator over items read in parallel, yielding them in index order.
+///
+/// Items that arrive ahead of the next index wait in a reordering buffer, which the workers
+/// never fill past a few items per thread. Items that could not be read are skipped, as with
+/// [`MassLynxReader::iter_spectra`]. Dropping the iterator stops the workers after their
+/// current item.
+pub struct OrderedParallelIter<T> {
+    receiver: Receiver<(usize, Option<T>)>,
+    pending: BTreeMap<usize, Option<T>>,
+    window: Arc<ReadWindow>,
+    next: usize,
+    len: usize,
+}
+
+impl<T> Iterator for OrderedParallelIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next < self.len {
+            if let Some(item) = self.pending.remove(&self.next) {
+                self.next += 1;
+                self.window.advance(self.next);
+                if item.is_some() {
+                    return item;
+                }
+                continue;
+            }
+            let (index, item) = self.receiver.recv().ok()?;
+            self.pending.insert(index, item);
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.len - self.next))
+    }
+}
+
+impl<T> Drop for OrderedParallelIter<T> {
+    fn drop(&mut self) {
+        self.window.stop();
+    }
+}
+
+impl MassLynxReader {
+    /// Read every spectrum in parallel on `threads` worker threads, or one per core if `None`
+    pub fn par_iter_spectra(
+        &self,
+        threads: Option<usize>,
+    ) -> MassLynxResult<OrderedParallelIter<Spectrum>> {
+        self.par_iter_with(self.len(), threads, MassLynxReader::get_spectrum)
+    }
+
+    /// Read every cycle in parallel on `threads` worker threads, or one per core if `None`
+    pub fn par_iter_cycles(
+        &self,
+        threads: Option<usize>,
+    ) -> MassLynxResult<OrderedParallelIter<Cycle>> {
+        self.par_iter_with(self.cycle_index().len(), threads, MassLynxReader::get_cycle)
+    }
+
+    fn par_iter_with<T: Send + 'static>(
+        &self,
+        len: usize,
+        threads: Option<usize>,
+        read: fn(&mut MassLynxReader, usize) -> Option<T>,
+    ) -> MassLynxResult<OrderedParallelIter<T>> {
+        let template = self.template();
+
+        // A dedicated pool keeps workers blocked on a slow consumer from starving the global
+        // pool the consumer may itself be using
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads.unwrap_or_default())
+            .build()
+            .map_err(|e| {
+                MassLynxError::new(
+                    MassLynxErrorKind::Thread,
+                    THREAD_POOL_ERROR_CODE,
+                    format!("Failed to start the reader thread pool: {e}"),
+                )
+            })?;
+        let n_threads = pool.current_num_threads();
+        let window = Arc::new(ReadWindow::new(len, n_threads * READ_AHEAD_PER_THREAD));
+        let (sender, receiver) = mpsc::sync_channel(window.size);
+        let (opened_sender, opened) = mpsc::channel();
+
+        // Each worker thread opens one reader and keeps it for every item it reads. The pool
+        // shuts down by itself once the workers return.
+        let workers = Arc::clone(&window);
+        pool.spawn_broadcast(move |_| {
+            let mut reader = match template.open() {
+                Ok(reader) => {
+                    let _ = opened_sender.send(Ok(()));
+                    reader
+                }
+                Err(e) => {
+                    let _ = opened_sender.send(Err(e));
+                    return;
+                }
+            };
+            while let Some(i) = workers.claim() {
+                if sender.send((i, read(&mut reader, i))).is_err() {
+                    break;
+                }
+            }
+        });
+
+        for result in opened.iter().take(n_threads) {
+            if let Err(e) = result {
+                window.stop();
+                return Err(e);
+            }
+        }
+
+        Ok(OrderedParallelIter {
+            receiver,
+            pending: BTreeMap::new(),
+            window,
+            next: 0,
+            len,
+        })
+    }
+}
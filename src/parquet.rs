@@ -0,0 +1,244 @@
+//! Stream a run into Parquet files, partitioned by function.
+//!
+//! Each selected function is written to its own `function=<N>` directory (numbered from 0) as
+//! one or more `part-<K>.parquet` files. Every file holds a long-format peak table with the
+//! columns of [`peak_schema`](crate::arrow::peak_schema), so ion mobility functions carry a
+//! drift time for every peak. The function's description is stored in the file's key-value
+//! metadata under these keys:
+//!
+//! | key | value |
+//! |---|---|
+//! | `masslynx.function` | The function number, starting from 0 |
+//! | `masslynx.function_type` | The [`MassLynxFunctionType`](crate::constants::MassLynxFunctionType) name |
+//! | `masslynx.ms_level` | The MS level |
+//! | `masslynx.ion_mobility_block_size` | The number of drift scans per cycle, 0 without ion mobility |
+//! | `masslynx.is_lockmass` | Whether this is the lock mass function |
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+
+use crate::arrow::{peak_schema, PeakTableBuilder};
+use crate::reader::{MassLynxReader, ScanFunction};
+use crate::MassLynxError;
+
+/// Errors that can occur while writing a run to Parquet
+#[derive(Debug)]
+pub enum ParquetExportError {
+    MassLynx(MassLynxError),
+    Parquet(ParquetError),
+    Io(io::Error),
+}
+
+impl Display for ParquetExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MassLynx(e) => write!(f, "{e}"),
+            Self::Parquet(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ParquetExportError {}
+
+impl From<MassLynxError> for ParquetExportError {
+    fn from(value: MassLynxError) -> Self {
+        Self::MassLynx(value)
+    }
+}
+
+impl From<ParquetError> for ParquetExportError {
+    fn from(value: ParquetError) -> Self {
+        Self::Parquet(value)
+    }
+}
+
+impl From<arrow_schema::ArrowError> for ParquetExportError {
+    fn from(value: arrow_schema::ArrowError) -> Self {
+        Self::Parquet(value.into())
+    }
+}
+
+impl From<io::Error> for ParquetExportError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Which parts of a run to write, and how to split them into files
+#[derive(Debug, Clone)]
+pub struct ParquetExportOptions {
+    /// Only write these functions. All MS functions are written if `None`
+    pub functions: Option<Vec<usize>>,
+    /// Only write cycles acquired in this retention time range, in minutes, inclusive
+    pub time_range: Option<(f64, f64)>,
+    /// The number of peaks to buffer before writing a row group
+    pub rows_per_batch: usize,
+    /// The number of peaks after which a new file is started for a function
+    pub rows_per_file: usize,
+}
+
+impl Default for ParquetExportOptions {
+    fn default() -> Self {
+        Self {
+            functions: None,
+            time_range: None,
+            rows_per_batch: 1_000_000,
+            rows_per_file: 50_000_000,
+        }
+    }
+}
+
+/// The open file and buffered peaks of one function's partition
+struct FunctionPartition {
+    directory: PathBuf,
+    metadata: Vec<KeyValue>,
+    builder: PeakTableBuilder,
+    writer: Option<ArrowWriter<fs::File>>,
+    rows_in_file: usize,
+    part: usize,
+    paths: Vec<PathBuf>,
+}
+
+impl FunctionPartition {
+    fn new(directory: PathBuf, function: &ScanFunction) -> Self {
+        let metadata = [
+            ("masslynx.function", function.function.to_string()),
+            ("masslynx.function_type", format!("{:?}", function.ftype)),
+            ("masslynx.ms_level", function.ms_level.to_string()),
+            (
+                "masslynx.ion_mobility_block_size",
+                function.ion_mobility_block_size.to_string(),
+            ),
+            ("masslynx.is_lockmass", function.is_lockmass.to_string()),
+        ]
+        .into_iter()
+        .map(|(k, v)| KeyValue::new(k.to_string(), v))
+        .collect();
+
+        Self {
+            directory,
+            metadata,
+            builder: PeakTableBuilder::new(),
+            writer: None,
+            rows_in_file: 0,
+            part: 0,
+            paths: Vec::new(),
+        }
+    }
+
+    fn open_writer(&mut self) -> Result<&mut ArrowWriter<fs::File>, ParquetExportError> {
+        if self.writer.is_none() {
+            fs::create_dir_all(&self.directory)?;
+            let path = self
+                .directory
+                .join(format!("part-{:04}.parquet", self.part));
+            let props = WriterProperties::builder()
+                .set_key_value_metadata(Some(self.metadata.clone()))
+                .build();
+            let writer =
+                ArrowWriter::try_new(fs::File::create(&path)?, peak_schema(), Some(props))?;
+            self.paths.push(path);
+            self.writer = Some(writer);
+        }
+        Ok(self.writer.as_mut().unwrap())
+    }
+
+    /// Write the buffered peaks, then start a new file if this one is full
+    fn flush(&mut self, options: &ParquetExportOptions) -> Result<(), ParquetExportError> {
+        if self.builder.is_empty() {
+            return Ok(());
+        }
+        let batch = self.builder.finish()?;
+        self.rows_in_file += batch.num_rows();
+        self.open_writer()?.write(&batch)?;
+        if self.rows_in_file >= options.rows_per_file {
+            self.close()?;
+            self.part += 1;
+            self.rows_in_file = 0;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), ParquetExportError> {
+        if let Some(writer) = self.writer.take() {
+            writer.close()?;
+        }
+        Ok(())
+    }
+}
+
+/// Write the peaks of a run to Parquet files under `directory`, as described in the
+/// [module documentation](self).
+///
+/// Lock mass scans are left out if the reader is skipping them. Returns the paths of the
+/// files written.
+pub fn write_run_parquet(
+    reader: &mut MassLynxReader,
+    directory: impl AsRef<Path>,
+    options: &ParquetExportOptions,
+) -> Result<Vec<PathBuf>, ParquetExportError> {
+    let directory = directory.as_ref();
+    let mut partitions: HashMap<usize, FunctionPartition> = HashMap::new();
+    for func in reader.functions() {
+        let selected = match options.functions.as_ref() {
+            Some(functions) => functions.contains(&func.function),
+            None => func.ms_level > 0,
+        };
+        if !selected || (func.is_lockmass && reader.get_lockmass_skipping()) {
+            continue;
+        }
+        partitions.insert(
+            func.function,
+            FunctionPartition::new(directory.join(format!("function={}", func.function)), func),
+        );
+    }
+
+    let cycles: Vec<_> = reader
+        .cycle_index()
+        .iter()
+        .filter(|e| partitions.contains_key(&e.function))
+        .filter(|e| {
+            options
+                .time_range
+                .map(|(start, end)| start <= e.time && e.time <= end)
+                .unwrap_or(true)
+        })
+        .copied()
+        .collect();
+
+    for entry in cycles {
+        let partition = partitions.get_mut(&entry.function).unwrap();
+        if entry.has_drift_time() {
+            let cycle = reader.try_get_cycle(entry.index)?;
+            partition.builder.append_cycle(&cycle);
+        } else if let Some(spectra) = reader.spectrum_range_of_cycle(entry.index) {
+            for i in spectra {
+                let spectrum = reader.try_get_spectrum(i)?;
+                partition.builder.append_spectrum(&spectrum);
+            }
+        }
+        if partition.builder.len() >= options.rows_per_batch {
+            partition.flush(options)?;
+        }
+    }
+
+    let mut paths = Vec::new();
+    let mut functions: Vec<_> = partitions.keys().copied().collect();
+    functions.sort();
+    for function in functions {
+        let mut partition = partitions.remove(&function).unwrap();
+        partition.flush(options)?;
+        partition.close()?;
+        paths.extend(partition.paths);
+    }
+    Ok(paths)
+}
@@ -2,23 +2,141 @@
 
 use std::{
     collections::HashMap,
-    fs,
-    io::{self, BufRead},
+    fmt::{self, Display},
+    fs, io,
+    ops::Range,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{mpsc, Arc},
+    thread,
 };
 
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use log::{debug, warn};
+
 use crate::{
-    base::MassLynxChromatogramReader,
+    base::{
+        decode_text, MassLynxChromatogramReader, CLOSED_READER_ERROR_CODE, DECODE_ERROR_CODE,
+        FUNCTION_OUT_OF_RANGE_ERROR_CODE, INDEX_OUT_OF_BOUNDS_ERROR_CODE,
+        INVALID_SDK_VALUE_ERROR_CODE, IO_ERROR_CODE, NOT_A_RAW_DIRECTORY_ERROR_CODE,
+        PATH_NOT_FOUND_ERROR_CODE, PREFETCH_THREAD_ERROR_CODE, RAW_PATHS_ERROR_CODE,
+        SKIPPED_LOCKMASS_ERROR_CODE,
+    },
     constants::{
-        AcquisitionParameter, LockMassParameter, MassLynxFunctionType, MassLynxHeaderItem,
-        MassLynxIonMode, MassLynxScanItem,
+        AcquisitionParameter, ItemKey, LockMassCompound, LockMassParameter,
+        MassLynxAcquisitionType, MassLynxFunctionType, MassLynxHeaderItem, MassLynxIonMode,
+        MassLynxScanItem, Polarity,
     },
-    AsMassLynxSource, MassLynxAnalogReader, MassLynxError, MassLynxInfoReader,
-    MassLynxLockMassProcessor, MassLynxParameters, MassLynxResult, MassLynxScanReader,
+    AsMassLynxSource, MassLynxAnalogReader, MassLynxError, MassLynxErrorKind, MassLynxInfoReader,
+    MassLynxLockMassProcessor, MassLynxParameters, MassLynxResult, MassLynxScanProcessor,
+    MassLynxScanReader,
 };
 
+pub(crate) fn index_out_of_bounds(what: &str, index: usize, len: usize) -> MassLynxError {
+    MassLynxError::new(
+        MassLynxErrorKind::InvalidIndex,
+        INDEX_OUT_OF_BOUNDS_ERROR_CODE,
+        format!("The {what} index {index} is out of bounds for a run with {len} entries"),
+    )
+}
+
+pub(crate) fn function_out_of_range(function: usize, len: usize) -> MassLynxError {
+    MassLynxError::new(
+        MassLynxErrorKind::FunctionOutOfRange,
+        FUNCTION_OUT_OF_RANGE_ERROR_CODE,
+        format!("The function {function} is out of bounds for a run with {len} functions"),
+    )
+}
+
+/// Describe a failure to list the files of a RAW directory
+fn raw_paths_error(error: io::Error) -> MassLynxError {
+    let (kind, error_code) = if error.kind() == io::ErrorKind::NotFound {
+        (MassLynxErrorKind::PathNotFound, PATH_NOT_FOUND_ERROR_CODE)
+    } else {
+        (MassLynxErrorKind::Io, RAW_PATHS_ERROR_CODE)
+    };
+    MassLynxError::new(
+        kind,
+        error_code,
+        format!("Failed to build file name registry: {error}"),
+    )
+}
+
+/// Check that `path` looks like a MassLynx RAW directory, explaining what is wrong with it if
+/// not. [`MassLynxReader::from_path`] does this before handing the path to the SDK, whose
+/// errors for these cases do not say what is wrong.
+pub fn validate_raw_directory<P: AsRef<Path>>(path: P) -> MassLynxResult<()> {
+    let path = path.as_ref();
+    let not_raw = |message: String, hint: Option<String>| {
+        MassLynxError::extended_new(
+            MassLynxErrorKind::NotARawDirectory,
+            NOT_A_RAW_DIRECTORY_ERROR_CODE,
+            message,
+            hint,
+        )
+    };
+    if !path.exists() {
+        return Err(MassLynxError::new(
+            MassLynxErrorKind::PathNotFound,
+            PATH_NOT_FOUND_ERROR_CODE,
+            format!("{} does not exist", path.display()),
+        ));
+    }
+    if !path.is_dir() {
+        return Err(not_raw(
+            format!("{} is a file, expected a .raw directory", path.display()),
+            path.parent()
+                .filter(|p| has_raw_extension(p))
+                .map(|p| format!("Did you mean {}?", p.display())),
+        ));
+    }
+
+    let names: Vec<String> = fs::read_dir(path)
+        .map_err(raw_paths_error)?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_lowercase())
+        .collect();
+    let has_function_data = names
+        .iter()
+        .any(|name| name.starts_with("_func") && name.ends_with(".dat"));
+    if has_function_data {
+        return Ok(());
+    }
+
+    let has_raw_files = names
+        .iter()
+        .any(|name| name == "_header.txt" || name == "_functns.inf" || name == "_extern.inf");
+    if has_raw_extension(path) || has_raw_files {
+        return Err(not_raw(
+            format!("{} exists but contains no _FUNC*.DAT files", path.display()),
+            Some(
+                "The acquisition may not have started, or the data files were not copied"
+                    .to_string(),
+            ),
+        ));
+    }
+
+    let nested: Vec<&String> = names.iter().filter(|name| name.ends_with(".raw")).collect();
+    let hint = match nested.as_slice() {
+        [] => None,
+        [one] => Some(format!("Did you mean {}?", path.join(one).display())),
+        many => Some(format!("It contains {} .raw directories", many.len())),
+    };
+    Err(not_raw(
+        format!(
+            "{} is not a MassLynx .raw directory, it has no _FUNC*.DAT or _HEADER.TXT files",
+            path.display()
+        ),
+        hint,
+    ))
+}
+
+fn has_raw_extension(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("raw"))
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpectrumIndexEntry {
     pub function: usize,
     pub cycle: usize,
@@ -48,6 +166,7 @@ impl SpectrumIndexEntry {
 }
 
 #[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CycleIndexEntry {
     pub function: usize,
     pub block: usize,
@@ -96,21 +215,21 @@ impl CycleIndexEntry {
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
-struct RawPaths {
+pub(crate) struct RawPaths {
     base_path: PathBuf,
-    function_paths: HashMap<usize, PathBuf>,
-    chromatogram_paths: HashMap<usize, PathBuf>,
+    pub(crate) function_paths: HashMap<usize, PathBuf>,
+    pub(crate) chromatogram_paths: HashMap<usize, PathBuf>,
 }
 
 impl RawPaths {
-    fn function_has_cdt(&self, function: usize) -> bool {
+    pub(crate) fn function_has_cdt(&self, function: usize) -> bool {
         self.function_paths
             .get(&function)
             .map(|p| p.with_extension("cdt").exists())
             .unwrap_or_default()
     }
 
-    fn from_path(base_path: PathBuf) -> io::Result<Self> {
+    pub(crate) fn from_path(base_path: PathBuf) -> io::Result<Self> {
         let mut this = Self {
             base_path,
             ..Self::default()
@@ -123,54 +242,114 @@ impl RawPaths {
         let root = self.base_path.as_path();
         let dirs = fs::read_dir(root)?;
 
-        let func_regex = regex::Regex::new(r"_func0*(\d+).dat").unwrap();
-        let chrom_regex = regex::Regex::new(r"_chro0*(\d+).dat").unwrap();
+        let func_regex = regex::Regex::new(r"^_func0*(\d+)\.dat$").unwrap();
+        let chrom_regex = regex::Regex::new(r"^_chro0*(\d+)\.dat$").unwrap();
 
-        for member in dirs.flatten() {
-            if member.file_type()?.is_dir() {
+        for member in dirs {
+            let member = match member {
+                Ok(member) => member,
+                Err(e) => {
+                    warn!("Skipping unreadable entry in {}: {e}", root.display());
+                    continue;
+                }
+            };
+            if member.file_type().map(|t| t.is_dir()).unwrap_or(true) {
                 continue;
             }
 
             let name = member.file_name().to_string_lossy().to_lowercase();
-            if name.starts_with("_func") && name.ends_with(".dat") {
-                if let Some(pat) = func_regex.captures(&name) {
-                    let func_num: usize = pat
-                        .get(1)
-                        .unwrap()
-                        .as_str()
-                        .parse::<usize>()
-                        .unwrap_or_else(|e| {
-                            panic!("Failed to parse function number from {name}: {e}")
-                        })
-                        .saturating_sub(1);
-                    self.function_paths.insert(func_num, member.path());
-                }
-            }
-            if name.starts_with("_chro") && name.ends_with(".dat") {
-                if let Some(pat) = chrom_regex.captures(&name) {
-                    let func_num: usize = pat
-                        .get(1)
-                        .unwrap()
-                        .as_str()
-                        .parse::<usize>()
-                        .unwrap_or_else(|e| {
-                            panic!("Failed to parse function number from {name}: {e}")
-                        })
-                        .saturating_sub(1);
-                    self.chromatogram_paths.insert(func_num, member.path());
+            let (paths, pattern) = if name.starts_with("_func") {
+                (&mut self.function_paths, &func_regex)
+            } else if name.starts_with("_chro") {
+                (&mut self.chromatogram_paths, &chrom_regex)
+            } else {
+                continue;
+            };
+            let Some(pat) = pattern.captures(&name) else {
+                continue;
+            };
+            match pat[1].parse::<usize>() {
+                Ok(func_num) => {
+                    paths.insert(func_num.saturating_sub(1), member.path());
                 }
+                Err(e) => warn!("Skipping {name}, its function number is not valid: {e}"),
             }
         }
 
         Ok(())
     }
 
-    fn path(&self) -> &PathBuf {
+    pub(crate) fn path(&self) -> &PathBuf {
         &self.base_path
     }
+
+    /// Check that the files each function needs are present and not empty
+    fn check_function_files(&self, functions: &[ScanFunction]) -> Vec<FileIssue> {
+        let mut issues = Vec::new();
+        for func in functions {
+            let Some(dat_path) = self.function_paths.get(&func.function) else {
+                issues.push(FileIssue::new(
+                    Some(func.function),
+                    format!("The data file for function {} is missing", func.function),
+                ));
+                continue;
+            };
+            let mut expected = vec![dat_path.clone(), dat_path.with_extension("idx")];
+            if func.has_drift_time() {
+                expected.push(dat_path.with_extension("cdt"));
+            }
+            for path in expected {
+                match fs::metadata(&path) {
+                    Ok(meta) if meta.len() == 0 && func.scan_count > 0 => {
+                        issues.push(FileIssue::new(
+                            Some(func.function),
+                            format!(
+                                "{} is empty but the function reports {} scans",
+                                path.display(),
+                                func.scan_count
+                            ),
+                        ))
+                    }
+                    Ok(_) => {}
+                    Err(e) => issues.push(FileIssue::new(
+                        Some(func.function),
+                        format!("Cannot read {}: {e}", path.display()),
+                    )),
+                }
+            }
+        }
+
+        for function in self.function_paths.keys() {
+            if !functions.iter().any(|f| f.function == *function) {
+                issues.push(FileIssue::new(
+                    Some(*function),
+                    format!(
+                        "A data file exists for function {function}, but the SDK does not list it"
+                    ),
+                ));
+            }
+        }
+        issues
+    }
+}
+
+/// A problem with the files of a RAW directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileIssue {
+    /// The function the problem affects, if it is specific to one function
+    pub function: Option<usize>,
+    pub message: String,
+}
+
+impl FileIssue {
+    pub fn new(function: Option<usize>, message: String) -> Self {
+        Self { function, message }
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScanFunction {
     pub function: usize,
     pub ftype: MassLynxFunctionType,
@@ -211,10 +390,54 @@ impl ScanFunction {
     }
 }
 
-#[derive(Debug, Default)]
+/// A one-line description, numbering functions from 1 as MassLynx does
+impl Display for ScanFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Function {}: {}", self.function + 1, self.ftype)?;
+        if self.is_lockmass {
+            write!(f, ", lock mass")?;
+        } else if self.ms_level > 0 {
+            write!(f, ", MS{}", self.ms_level)?;
+        }
+        write!(f, ", {} scans", self.scan_count)?;
+        if self.has_drift_time() {
+            write!(f, ", {} drift bins", self.ion_mobility_block_size)?;
+        }
+        if self.is_sonar() {
+            write!(f, ", SONAR")?;
+        }
+        Ok(())
+    }
+}
+
+/// The reference m/z and tolerance used to lock mass correct a run
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LockMassSettings {
+    pub mass: f32,
+    /// The m/z tolerance used to find the reference peak, the SDK's default if `None`
+    pub tolerance: Option<f32>,
+}
+
+impl LockMassSettings {
+    pub fn new(mass: f32, tolerance: Option<f32>) -> Self {
+        Self { mass, tolerance }
+    }
+
+    /// Use the standard reference ion of `compound` in `polarity`, or `None` if the compound
+    /// has no reference ion in that polarity
+    pub fn from_compound(compound: LockMassCompound, polarity: Polarity) -> Option<Self> {
+        compound
+            .reference_mz(polarity)
+            .map(|mass| Self::new(mass, None))
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
 struct ScanReadingOptions {
     skip_lockmass: bool,
     load_signal: bool,
+    centroid: bool,
 }
 
 impl ScanReadingOptions {
@@ -222,6 +445,7 @@ impl ScanReadingOptions {
         Self {
             skip_lockmass,
             load_signal,
+            centroid: false,
         }
     }
 
@@ -240,6 +464,14 @@ impl ScanReadingOptions {
     fn load_signal(&self) -> bool {
         self.load_signal
     }
+
+    fn centroid(&self) -> bool {
+        self.centroid
+    }
+
+    fn set_centroid(&mut self, centroid: bool) {
+        self.centroid = centroid;
+    }
 }
 
 pub struct MassLynxReader {
@@ -249,26 +481,247 @@ pub struct MassLynxReader {
     chromatogram_reader: MassLynxChromatogramReader,
     lockmass_processor: MassLynxLockMassProcessor,
     analog_reader: Option<MassLynxAnalogReader>,
+    scan_processor: Option<MassLynxScanProcessor>,
     cycle_index: Vec<CycleIndexEntry>,
     spectrum_index: Vec<SpectrumIndexEntry>,
+    cycle_spectrum_offsets: Vec<usize>,
     scan_reading_options: ScanReadingOptions,
     functions: Vec<ScanFunction>,
+    lock_mass_target: Option<LockMassTarget>,
+    chromatogram_cache: ChromatogramCache,
+    parameters: ParameterHandles,
+    /// The drift time of each drift bin, filled in on first use
+    drift_time_axis: Vec<f64>,
 }
 
-impl MassLynxReader {
-    pub fn from_path(path: &str) -> MassLynxResult<Self> {
-        let info_reader = MassLynxInfoReader::from_path(&path)?;
+/// Parameter handles reused across calls, rather than creating and destroying one for every
+/// scan. The requested scan item keys are cleared before each read and empty values are
+/// skipped, so an item the SDK leaves unset for one scan is never read back with the value
+/// from an earlier scan.
+struct ParameterHandles {
+    header_items: MassLynxParameters,
+    scan_items: MassLynxParameters,
+}
+
+impl ParameterHandles {
+    fn new() -> MassLynxResult<Self> {
+        Ok(Self {
+            header_items: MassLynxParameters::new()?,
+            scan_items: MassLynxParameters::new()?,
+        })
+    }
+}
+
+/// How the run was last lock mass corrected, to be repeated when the reader is reopened
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LockMassTarget {
+    Manual(f32, Option<f32>),
+    Auto { force: bool },
+}
+
+/// Whole-run chromatograms that are expensive to re-read and merge
+#[derive(Debug, Default, Clone)]
+struct ChromatogramCache {
+    tic: Option<(Vec<f32>, Vec<f32>)>,
+    bpi: Option<(Vec<f32>, Vec<f32>)>,
+}
+
+impl ChromatogramCache {
+    fn clear(&mut self) {
+        self.tic = None;
+        self.bpi = None;
+    }
+}
+
+/// Formats a value with its [`Display`] implementation inside [`fmt::Debug`] output
+struct DisplayAsDebug<T>(T);
+
+impl<T: Display> fmt::Debug for DisplayAsDebug<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Debug for MassLynxReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MassLynxReader")
+            .field("path", &self.path())
+            .field("is_open", &self.is_open())
+            .field("acquisition_type", &self.acquisition_type())
+            .field("spectra", &self.len())
+            .field("cycles", &self.cycle_index.len())
+            .field("lock_mass", &DisplayAsDebug(self.lock_mass_status()))
+            .field(
+                "functions",
+                &self
+                    .functions
+                    .iter()
+                    .map(DisplayAsDebug)
+                    .collect::<Vec<_>>(),
+            )
+            .field("scan_reading_options", &self.scan_reading_options)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The set of SDK handles a [`MassLynxReader`] needs to read a RAW directory
+struct ReaderHandles {
+    info_reader: MassLynxInfoReader,
+    scan_reader: MassLynxScanReader,
+    chromatogram_reader: MassLynxChromatogramReader,
+    analog_reader: Option<MassLynxAnalogReader>,
+    lockmass_processor: MassLynxLockMassProcessor,
+}
+
+impl ReaderHandles {
+    fn open(path: &str) -> MassLynxResult<Self> {
+        let info_reader = MassLynxInfoReader::from_path(path)?;
         let scan_reader = MassLynxScanReader::from_source(&info_reader)?;
         let chromatogram_reader = MassLynxChromatogramReader::from_source(&info_reader)?;
         let analog_reader = MassLynxAnalogReader::from_source(&info_reader).ok();
         let mut lockmass_processor = MassLynxLockMassProcessor::new()?;
         lockmass_processor.set_raw_data_from_reader(&scan_reader)?;
+        Ok(Self {
+            info_reader,
+            scan_reader,
+            chromatogram_reader,
+            analog_reader,
+            lockmass_processor,
+        })
+    }
+}
 
-        let path = RawPaths::from_path(PathBuf::from(path)).map_err(|e| MassLynxError {
-            error_code: 9999,
-            message: format!("Failed to build file name registry: {e}"),
-            extended_message: None,
-        })?;
+/// An iterator over items read ahead on a background thread, see
+/// [`MassLynxReader::iter_spectra_prefetch`]. Dropping it stops the thread after its current
+/// item.
+#[derive(Debug)]
+pub struct PrefetchIter<T> {
+    receiver: mpsc::Receiver<T>,
+}
+
+impl<T> Iterator for PrefetchIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// An iterator that owns its [`MassLynxReader`], see [`MassLynxReader::into_iter`] and
+/// [`MassLynxReader::into_cycles`]. Items that could not be read are skipped.
+///
+/// Like the reader, this holds SDK handles and is not [`Send`], so it cannot be moved to
+/// another thread. To read on a background thread, use
+/// [`MassLynxReader::iter_spectra_prefetch`] or [`MassLynxReader::iter_cycles_prefetch`],
+/// which open their own copy of the reader there, or open a reader with
+/// [`MassLynxReader::from_path`] inside the thread.
+#[derive(Debug)]
+pub struct IntoIter<T> {
+    reader: MassLynxReader,
+    index: usize,
+    len: usize,
+    read: fn(&mut MassLynxReader, usize) -> Option<T>,
+}
+
+impl<T> IntoIter<T> {
+    /// Stop iterating and get the reader back
+    pub fn into_reader(self) -> MassLynxReader {
+        self.reader
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.len {
+            let index = self.index;
+            self.index += 1;
+            if let Some(item) = (self.read)(&mut self.reader, index) {
+                return Some(item);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.len - self.index))
+    }
+}
+
+impl IntoIterator for MassLynxReader {
+    type Item = Spectrum;
+    type IntoIter = IntoIter<Spectrum>;
+
+    /// Read every spectrum, consuming the reader, as with [`MassLynxReader::iter_spectra`]
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            len: self.len(),
+            reader: self,
+            index: 0,
+            read: Self::get_spectrum,
+        }
+    }
+}
+
+/// The in-memory state of a [`MassLynxReader`], without any SDK handles, so that it can be
+/// sent to another thread and opened there
+#[derive(Debug, Clone)]
+pub(crate) struct ReaderTemplate {
+    path: RawPaths,
+    cycle_index: Vec<CycleIndexEntry>,
+    spectrum_index: Vec<SpectrumIndexEntry>,
+    cycle_spectrum_offsets: Vec<usize>,
+    scan_reading_options: ScanReadingOptions,
+    functions: Vec<ScanFunction>,
+    lock_mass_target: Option<LockMassTarget>,
+    drift_time_axis: Vec<f64>,
+}
+
+impl ReaderTemplate {
+    pub(crate) fn open(&self) -> MassLynxResult<MassLynxReader> {
+        let ReaderHandles {
+            info_reader,
+            scan_reader,
+            chromatogram_reader,
+            analog_reader,
+            lockmass_processor,
+        } = ReaderHandles::open(&self.path.path().to_string_lossy())?;
+        let mut reader = MassLynxReader {
+            path: self.path.clone(),
+            info_reader,
+            scan_reader,
+            chromatogram_reader,
+            analog_reader,
+            lockmass_processor,
+            scan_processor: None,
+            cycle_index: self.cycle_index.clone(),
+            spectrum_index: self.spectrum_index.clone(),
+            cycle_spectrum_offsets: self.cycle_spectrum_offsets.clone(),
+            scan_reading_options: self.scan_reading_options,
+            functions: self.functions.clone(),
+            lock_mass_target: self.lock_mass_target,
+            chromatogram_cache: Default::default(),
+            parameters: ParameterHandles::new()?,
+            drift_time_axis: self.drift_time_axis.clone(),
+        };
+        reader.apply_lock_mass_target()?;
+        Ok(reader)
+    }
+}
+
+impl MassLynxReader {
+    pub fn from_path(path: &str) -> MassLynxResult<Self> {
+        validate_raw_directory(path)?;
+        let ReaderHandles {
+            info_reader,
+            scan_reader,
+            chromatogram_reader,
+            analog_reader,
+            lockmass_processor,
+        } = ReaderHandles::open(path)?;
+
+        let path = RawPaths::from_path(PathBuf::from(path)).map_err(raw_paths_error)?;
 
         let mut this = Self {
             path,
@@ -277,10 +730,16 @@ impl MassLynxReader {
             chromatogram_reader,
             analog_reader,
             lockmass_processor,
+            scan_processor: None,
             cycle_index: Default::default(),
             spectrum_index: Default::default(),
+            cycle_spectrum_offsets: Default::default(),
             scan_reading_options: ScanReadingOptions::new(true, true),
             functions: Vec::new(),
+            lock_mass_target: None,
+            chromatogram_cache: Default::default(),
+            parameters: ParameterHandles::new()?,
+            drift_time_axis: Vec::new(),
         };
 
         this.functions = this.describe_functions()?;
@@ -288,6 +747,155 @@ impl MassLynxReader {
         Ok(this)
     }
 
+    /// Release all of the SDK handles held by this reader so that the RAW directory is no
+    /// longer locked by this process.
+    ///
+    /// The function descriptions and the spectrum and cycle indices are kept in memory, so
+    /// the reader can be resumed with [`MassLynxReader::reopen`] without rebuilding them.
+    /// Reading signal or metadata while closed will fail.
+    pub fn close(&mut self) {
+        // The processors hold a reference to the scan reader, so release them first
+        self.lockmass_processor = Default::default();
+        self.scan_processor = None;
+        self.analog_reader = None;
+        self.chromatogram_reader = Default::default();
+        self.scan_reader = Default::default();
+        self.info_reader = Default::default();
+    }
+
+    /// Re-create the SDK handles released by [`MassLynxReader::close`], re-using the
+    /// in-memory index. If a lock mass was set with [`MassLynxReader::set_lock_mass`], it
+    /// is applied again.
+    ///
+    /// This does nothing if the reader is already open.
+    pub fn reopen(&mut self) -> MassLynxResult<()> {
+        if self.is_open() {
+            return Ok(());
+        }
+        let path = self.path().to_string_lossy().to_string();
+        let handles = ReaderHandles::open(&path)?;
+        self.info_reader = handles.info_reader;
+        self.scan_reader = handles.scan_reader;
+        self.chromatogram_reader = handles.chromatogram_reader;
+        self.analog_reader = handles.analog_reader;
+        self.lockmass_processor = handles.lockmass_processor;
+
+        self.apply_lock_mass_target()
+    }
+
+    /// Repeat the last lock mass correction on freshly opened handles
+    fn apply_lock_mass_target(&mut self) -> MassLynxResult<()> {
+        match self.lock_mass_target {
+            Some(LockMassTarget::Manual(mass, tolerance)) => {
+                self.set_lock_mass(mass, tolerance)?;
+            }
+            Some(LockMassTarget::Auto { force }) => {
+                self.auto_lockmass(force)?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Open another reader on the same RAW directory with its own SDK handles. The index,
+    /// reading options and lock mass correction are copied from this reader rather than
+    /// rebuilt, but cached chromatograms are not.
+    pub fn try_clone(&self) -> MassLynxResult<Self> {
+        self.template().open()
+    }
+
+    /// Capture everything needed to open a copy of this reader, possibly on another thread
+    pub(crate) fn template(&self) -> ReaderTemplate {
+        ReaderTemplate {
+            path: self.path.clone(),
+            cycle_index: self.cycle_index.clone(),
+            spectrum_index: self.spectrum_index.clone(),
+            cycle_spectrum_offsets: self.cycle_spectrum_offsets.clone(),
+            scan_reading_options: self.scan_reading_options,
+            functions: self.functions.clone(),
+            lock_mass_target: self.lock_mass_target,
+            drift_time_axis: self.drift_time_axis.clone(),
+        }
+    }
+
+    /// Re-read a run that is still being acquired, adding any cycles written since the index
+    /// was last built to the end of the cycle and spectrum indices.
+    ///
+    /// Existing cycle and spectrum indices keep referring to the same data. Returns the number
+    /// of new cycles.
+    ///
+    /// The SDK handles are re-created so that the SDK sees the newly written data, and the
+    /// cached chromatograms are dropped. See [`MassLynxReader::refresh_index`] to only
+    /// re-query the open handles.
+    pub fn refresh(&mut self) -> MassLynxResult<usize> {
+        self.check_open()?;
+        self.close();
+        self.reopen()?;
+        self.path = RawPaths::from_path(self.path().to_path_buf()).map_err(raw_paths_error)?;
+        self.refresh_index()
+    }
+
+    /// Add the cycles of any function whose scan count grew since the index was last built to
+    /// the end of the cycle and spectrum indices, without rebuilding them or re-creating the
+    /// SDK handles.
+    ///
+    /// Returns the number of new cycles. The cached chromatograms are dropped if there were
+    /// any.
+    pub fn refresh_index(&mut self) -> MassLynxResult<usize> {
+        self.check_open()?;
+        let functions = self.describe_functions()?;
+        let mut new_cycles = Vec::new();
+        for func in functions.iter() {
+            if func.ms_level == 0 {
+                continue;
+            }
+            let known = self
+                .functions
+                .get(func.function)
+                .map(|f| f.scan_count)
+                .unwrap_or_default();
+            for i in known..func.scan_count {
+                let rt = self.info_reader.get_retention_time(func.function, i)?;
+                new_cycles.push(CycleIndexEntry::new(
+                    func.function,
+                    i,
+                    rt,
+                    func.ion_mobility_block_size,
+                    0,
+                ));
+            }
+        }
+
+        let n_new = new_cycles.len();
+        self.functions = functions;
+        if n_new > 0 {
+            self.append_cycles(new_cycles);
+            self.chromatogram_cache.clear();
+        }
+        Ok(n_new)
+    }
+
+    /// Check whether the reader currently holds open SDK handles
+    pub fn is_open(&self) -> bool {
+        !self.info_reader.as_mass_lynx_source().is_null()
+    }
+
+    fn check_open(&self) -> MassLynxResult<()> {
+        if self.is_open() {
+            Ok(())
+        } else {
+            Err(MassLynxError::extended_new(
+                MassLynxErrorKind::ReaderClosed,
+                CLOSED_READER_ERROR_CODE,
+                "The reader is closed".to_string(),
+                Some(format!(
+                    "Call `reopen` to resume reading {}",
+                    self.path().display()
+                )),
+            ))
+        }
+    }
+
     /// Describe the scan functions found in this run
     pub fn functions(&self) -> &[ScanFunction] {
         &self.functions
@@ -311,7 +919,7 @@ impl MassLynxReader {
                 0
             };
 
-            let ms_level = self.translate_function_type_to_ms_level(fnum)?;
+            let ms_level = ftype.default_ms_level();
 
             let scan_items = self.info_reader.get_scan_items(fnum)?.iter_keys().collect();
 
@@ -330,23 +938,79 @@ impl MassLynxReader {
         Ok(functions)
     }
 
-    /// Get the index of the lock mass function
+    /// Get the SDK's display name for the type of `which_function`
+    pub fn function_type_string(&mut self, which_function: usize) -> MassLynxResult<String> {
+        self.check_open()?;
+        let ftype = self
+            .info_reader
+            .get_function_type(which_function)
+            .map_err(|e| self.augment_function_error(e))?;
+        self.info_reader.get_function_type_string(ftype)
+    }
+
+    /// Get the ion mode `which_function` was acquired in
+    pub fn ion_mode(&mut self, which_function: usize) -> MassLynxResult<MassLynxIonMode> {
+        self.check_open()?;
+        self.info_reader
+            .get_ion_mode(which_function)
+            .map_err(|e| self.augment_function_error(e))
+    }
+
+    /// Get the SDK's display name for the ion mode of `which_function`
+    pub fn ion_mode_string(&mut self, which_function: usize) -> MassLynxResult<String> {
+        let ion_mode = self.ion_mode(which_function)?;
+        self.info_reader.get_ion_mode_string(ion_mode)
+    }
+
+    /// Check if `which_function` was acquired in profile mode
+    pub fn is_continuum(&mut self, which_function: usize) -> MassLynxResult<bool> {
+        self.check_open()?;
+        self.info_reader
+            .is_continuum(which_function)
+            .map_err(|e| self.augment_function_error(e))
+    }
+
+    /// Get the m/z range `which_function` was acquired over
+    pub fn acquisition_mass_range(&self, which_function: usize) -> MassLynxResult<(f64, f64)> {
+        self.check_open()?;
+        self.info_reader
+            .get_acquisition_mass_range(which_function)
+            .map_err(|e| self.augment_function_error(e))
+    }
+
+    /// Get the retention time range, in minutes, `which_function` was acquired over
+    pub fn acquisition_time_range(&mut self, which_function: usize) -> MassLynxResult<(f32, f32)> {
+        self.check_open()?;
+        self.info_reader
+            .get_acquisition_time_range(which_function)
+            .map_err(|e| self.augment_function_error(e))
+    }
+
+    /// Get the index of the lock mass function, or `None` if the run does not have one
     pub fn get_lock_mass_function(&self) -> Option<usize> {
         self.info_reader
             .get_lock_mass_function()
             .ok()
-            .map(|(_, func)| func)
+            .and_then(|(has_lock_mass, func)| has_lock_mass.then_some(func))
     }
 
     /// Check if the run is lock mass corrected
     pub fn is_lock_mass_corrected(&mut self) -> bool {
+        if !self.is_open() {
+            return false;
+        }
         self.info_reader
             .is_lock_mass_corrected()
             .unwrap_or_default()
     }
 
-    /// Manually set the lock mass target
+    /// Manually set the lock mass target. The cached chromatograms are dropped.
+    ///
+    /// The target is only remembered for [`MassLynxReader::reopen`] and
+    /// [`MassLynxReader::try_clone`] once the correction has been applied.
     pub fn set_lock_mass(&mut self, mass: f32, tolerance: Option<f32>) -> MassLynxResult<()> {
+        self.check_open()?;
+        self.invalidate_lock_mass_dependents();
         let mut params = MassLynxParameters::new()?;
 
         params.set(LockMassParameter::MASS, mass.to_string())?;
@@ -364,12 +1028,122 @@ impl MassLynxReader {
 
         if self.lockmass_processor.can_lock_mass_correct()? {
             self.lockmass_processor.lock_mass_correct()?;
+            self.lock_mass_target = Some(LockMassTarget::Manual(mass, tolerance));
         }
         Ok(())
     }
 
+    /// Set the lock mass target from `settings`, see [`MassLynxReader::set_lock_mass`]
+    pub fn set_lock_mass_settings(&mut self, settings: &LockMassSettings) -> MassLynxResult<()> {
+        self.set_lock_mass(settings.mass, settings.tolerance)
+    }
+
+    /// Lock mass correct the run with the reference settings recorded by the acquisition
+    /// method. If `force` is set, correct the run even if it is already corrected.
+    ///
+    /// Returns whether the correction was applied. The cached chromatograms are dropped.
+    pub fn auto_lockmass(&mut self, force: bool) -> MassLynxResult<bool> {
+        self.check_open()?;
+        self.invalidate_lock_mass_dependents();
+        let applied = self.lockmass_processor.auto_lock_mass_correct(force)?;
+        if applied {
+            self.lock_mass_target = Some(LockMassTarget::Auto { force });
+        }
+        Ok(applied)
+    }
+
+    /// Check if the lock mass processor can correct the run with its current parameters
+    pub fn can_lock_mass_correct(&self) -> bool {
+        if !self.is_open() {
+            return false;
+        }
+        self.lockmass_processor
+            .can_lock_mass_correct()
+            .unwrap_or_default()
+    }
+
+    /// Remove the lock mass correction from the run. The cached chromatograms are dropped.
+    pub fn remove_lockmass_correction(&mut self) -> MassLynxResult<()> {
+        self.check_open()?;
+        self.invalidate_lock_mass_dependents();
+        self.lockmass_processor.remove_lock_mass_correction()?;
+        self.lock_mass_target = None;
+        Ok(())
+    }
+
+    /// Drop everything read through the lock mass processor, so that data read before and
+    /// after changing the correction are never mixed
+    fn invalidate_lock_mass_dependents(&mut self) {
+        self.chromatogram_cache.clear();
+    }
+
+    /// Get the lock mass correction gain applied at `retention_time` (in minutes)
+    pub fn lock_mass_gain(&self, retention_time: f32) -> MassLynxResult<f32> {
+        self.check_open()?;
+        self.lockmass_processor
+            .get_lock_mass_correction(retention_time)
+    }
+
+    /// Sample [`MassLynxReader::lock_mass_gain`] every `step_minutes` from the first cycle of
+    /// the run to the last, always including the last.
+    ///
+    /// If `step_minutes` is not positive, only the first cycle's time is sampled. A step that
+    /// would sample more times than the run has cycles is widened to the run's length divided
+    /// by its number of cycles. The trace is empty if the run has no cycles.
+    pub fn lockmass_gain_curve(&self, step_minutes: f32) -> MassLynxResult<Trace> {
+        self.check_open()?;
+        let mut times = Vec::new();
+        if let (Some(first), Some(last)) = (self.cycle_index.first(), self.cycle_index.last()) {
+            let (start, end) = (first.time as f32, last.time as f32);
+            times.push(start);
+            if step_minutes > 0.0 {
+                // Bound the number of SDK calls however small the step is
+                let step = step_minutes.max((end - start) / self.cycle_index.len() as f32);
+                let n_steps = ((end - start) / step).ceil() as usize;
+                times.extend((1..n_steps).map(|i| start + step * i as f32));
+                if end > start {
+                    times.push(end);
+                }
+            }
+        }
+        let gains = times
+            .iter()
+            .map(|t| self.lock_mass_gain(*t))
+            .collect::<MassLynxResult<Vec<f32>>>()?;
+        Ok(Trace::new(
+            "Lock Mass Gain".to_string(),
+            String::new(),
+            times,
+            gains,
+        ))
+    }
+
+    /// Get the reference peaks the lock mass processor considered, as (m/z, intensity) pairs
+    pub fn lockmass_candidates(&mut self) -> MassLynxResult<Vec<(f32, f32)>> {
+        self.check_open()?;
+        let mut masses = Vec::new();
+        let mut intensities = Vec::new();
+        self.lockmass_processor
+            .get_candidates(&mut masses, &mut intensities)?;
+        Ok(masses.into_iter().zip(intensities).collect())
+    }
+
+    /// Find the lock mass candidate closest to `mass` within `tolerance`, see
+    /// [`MassLynxReader::lockmass_candidates`]
+    pub fn match_lockmass_candidate(
+        &mut self,
+        mass: f32,
+        tolerance: f32,
+    ) -> MassLynxResult<Option<(f32, f32)>> {
+        let candidates = self.lockmass_candidates()?;
+        Ok(candidates
+            .into_iter()
+            .filter(|(mz, _)| (mz - mass).abs() <= tolerance)
+            .min_by(|(a, _), (b, _)| (a - mass).abs().total_cmp(&(b - mass).abs())))
+    }
+
     fn augment_function_error(&self, mut error: MassLynxError) -> MassLynxError {
-        if error.error_code == 14 {
+        if error.kind() == MassLynxErrorKind::FunctionOutOfRange {
             let f: Vec<_> = self
                 .functions()
                 .iter()
@@ -381,25 +1155,6 @@ impl MassLynxReader {
         error
     }
 
-    fn translate_function_type_to_ms_level(&mut self, fnum: usize) -> MassLynxResult<u8> {
-        let ftype = self
-            .info_reader
-            .get_function_type(fnum)
-            .map_err(|e| self.augment_function_error(e))?;
-        match ftype {
-            MassLynxFunctionType::MS
-            | MassLynxFunctionType::TOF
-            | MassLynxFunctionType::TOFM
-            | MassLynxFunctionType::PAR
-            | MassLynxFunctionType::MTOF
-            | MassLynxFunctionType::TOFP => Ok(1),
-            MassLynxFunctionType::MS2 | MassLynxFunctionType::TOFD | MassLynxFunctionType::DAU => {
-                Ok(2)
-            }
-            _ => Ok(0),
-        }
-    }
-
     fn build_index(&mut self) -> MassLynxResult<()> {
         let mut cycle_index = Vec::new();
 
@@ -420,29 +1175,55 @@ impl MassLynxReader {
             }
         }
 
+        self.cycle_index.clear();
+        self.spectrum_index.clear();
+        self.cycle_spectrum_offsets.clear();
+        self.append_cycles(cycle_index);
+
+        Ok(())
+    }
+
+    /// Sort `cycle_index` by time and add it to the end of the cycle and spectrum indices
+    fn append_cycles(&mut self, mut cycle_index: Vec<CycleIndexEntry>) {
         cycle_index.sort_by(|a, b| a.time.total_cmp(&b.time));
         // let mut function_index: HashMap<usize, Vec<usize>> = HashMap::default();
-        let mut spectrum_index = Vec::with_capacity(cycle_index.len());
+        self.spectrum_index.reserve(cycle_index.len());
+        self.cycle_spectrum_offsets.reserve(cycle_index.len());
+        let offset = self.cycle_index.len();
         for (i, entry) in cycle_index.iter_mut().enumerate() {
-            entry.index = i;
+            entry.index = offset + i;
+            self.cycle_spectrum_offsets.push(self.spectrum_index.len());
             // function_index.entry(entry.function).or_default().push(i);
             if entry.im_block_size > 0 {
                 for j in 0..entry.im_block_size {
-                    spectrum_index.push(SpectrumIndexEntry::new(
+                    self.spectrum_index.push(SpectrumIndexEntry::new(
                         entry.function,
                         entry.block,
                         Some(j as u32),
                     ))
                 }
             } else {
-                spectrum_index.push(SpectrumIndexEntry::new(entry.function, entry.block, None))
+                self.spectrum_index
+                    .push(SpectrumIndexEntry::new(entry.function, entry.block, None))
             }
         }
-
-        self.cycle_index = cycle_index;
-        self.spectrum_index = spectrum_index;
-
-        Ok(())
+        self.cycle_index.extend(cycle_index);
+    }
+
+    /// Check the RAW directory for missing or empty function files, and for function files
+    /// the SDK does not know about.
+    ///
+    /// This only looks at the directory listing and file sizes, it does not read any signal.
+    pub fn check_files(&self) -> Vec<FileIssue> {
+        let mut issues = self.path.check_function_files(&self.functions);
+        let header = self.path().join("_HEADER.TXT");
+        if !header.exists() && !self.path().join("_header.txt").exists() {
+            issues.push(FileIssue::new(
+                None,
+                "The _HEADER.TXT file is missing".to_string(),
+            ));
+        }
+        issues
     }
 
     /// Get the base path of the RAW directory
@@ -465,47 +1246,218 @@ impl MassLynxReader {
         self.spectrum_index.len()
     }
 
+    /// Infer how the run was acquired from its function table. Lock mass functions are
+    /// ignored, and [`MassLynxAcquisitionType::UNKNOWN`] is returned for runs that do not fit
+    /// any of the data independent or dependent schemes, e.g. a single MS function.
+    pub fn acquisition_type(&self) -> MassLynxAcquisitionType {
+        let functions: Vec<&ScanFunction> = self
+            .functions
+            .iter()
+            .filter(|f| !f.is_lockmass && f.ms_level > 0)
+            .collect();
+        let has_drift_time = functions.iter().any(|f| f.has_drift_time());
+        if functions.iter().any(|f| f.is_sonar()) {
+            MassLynxAcquisitionType::SONAR
+        } else if functions.iter().any(|f| f.ftype.is_msms()) {
+            if has_drift_time {
+                MassLynxAcquisitionType::HDDDA
+            } else {
+                MassLynxAcquisitionType::DDA
+            }
+        } else if functions.len() > 1 {
+            if has_drift_time {
+                MassLynxAcquisitionType::HDMSE
+            } else {
+                MassLynxAcquisitionType::MSE
+            }
+        } else {
+            MassLynxAcquisitionType::UNKNOWN
+        }
+    }
+
+    /// Describe the lock mass function, and how the run is corrected if it is
+    fn lock_mass_status(&self) -> String {
+        let Some(lockmass) = self.functions.iter().find(|f| f.is_lockmass) else {
+            return "none".to_string();
+        };
+        let corrected = self.is_open()
+            && self
+                .lockmass_processor
+                .is_lock_mass_corrected()
+                .unwrap_or_default();
+        let status = match (corrected, self.lock_mass_target) {
+            (false, _) => "not corrected".to_string(),
+            (true, Some(LockMassTarget::Manual(mass, _))) => format!("corrected at {mass} m/z"),
+            (true, Some(LockMassTarget::Auto { .. })) => "corrected automatically".to_string(),
+            (true, None) => "corrected".to_string(),
+        };
+        format!("function {}, {status}", lockmass.function + 1)
+    }
+
+    /// Describe the run in a few lines: its path, acquisition type, size, lock mass status
+    /// and function table
+    pub fn summary(&self) -> String {
+        let mut lines = vec![
+            format!("Path: {}", self.path().display()),
+            format!("Acquisition type: {:?}", self.acquisition_type()),
+            format!("Spectra: {}", self.len()),
+            format!("Cycles: {}", self.cycle_index.len()),
+            format!("Lock mass: {}", self.lock_mass_status()),
+        ];
+        if !self.is_open() {
+            lines.push("Closed".to_string());
+        }
+        lines.push(format!("Functions: {}", self.functions.len()));
+        lines.extend(self.functions.iter().map(|f| format!("  {f}")));
+        lines.join("\n")
+    }
+
+    /// Find the index of the first cycle acquired at or after `time` (in minutes) using a
+    /// binary search over the cycle index.
+    ///
+    /// Returns `None` if `time` is after the last cycle in the run.
+    pub fn cycle_index_for_time(&self, time: f64) -> Option<usize> {
+        let i = self.cycle_index.partition_point(|e| e.time < time);
+        (i < self.cycle_index.len()).then_some(i)
+    }
+
+    /// Find the index of the first spectrum of the first cycle acquired at or after `time`
+    /// (in minutes).
+    ///
+    /// Returns `None` if `time` is after the last cycle in the run.
+    pub fn spectrum_index_for_time(&self, time: f64) -> Option<usize> {
+        let i = self.cycle_index_for_time(time)?;
+        self.cycle_spectrum_offsets.get(i).copied()
+    }
+
+    /// Find the range of scans of `which_function` acquired between `start_time` and `end_time`
+    /// (in minutes), inclusive.
+    ///
+    /// Returns `None` if the function has no scans in that interval.
+    pub fn scan_range_for_time(
+        &self,
+        which_function: usize,
+        start_time: f64,
+        end_time: f64,
+    ) -> Option<(usize, usize)> {
+        let mut scans = self
+            .cycle_index
+            .iter()
+            .filter(|e| e.function == which_function && start_time <= e.time && e.time <= end_time)
+            .map(|e| e.block);
+        let first = scans.next()?;
+        Some(scans.fold((first, first), |(lo, hi), i| (lo.min(i), hi.max(i))))
+    }
+
+    /// Get the range of spectrum indices that belong to the cycle at `index`
+    pub fn spectrum_range_of_cycle(&self, index: usize) -> Option<Range<usize>> {
+        let start = *self.cycle_spectrum_offsets.get(index)?;
+        let end = self
+            .cycle_spectrum_offsets
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.spectrum_index.len());
+        Some(start..end)
+    }
+
+    /// Find the index of the spectrum whose native ID is `id`
+    ///
+    /// Drift scans are numbered within their cycle, so for ion mobility functions this
+    /// finds the first spectrum with a matching native ID.
+    pub fn spectrum_index_of_id(&self, id: &str) -> Option<usize> {
+        self.spectrum_index.iter().position(|e| e.native_id() == id)
+    }
+
+    /// Find the index of the cycle whose native ID is `id`
+    pub fn cycle_index_of_id(&self, id: &str) -> Option<usize> {
+        self.cycle_index.iter().position(|e| e.native_id() == id)
+    }
+
+    /// Get the scan items recorded for `which_function` along with the SDK's display name for
+    /// each
+    pub fn scan_item_names(
+        &self,
+        which_function: usize,
+    ) -> MassLynxResult<Vec<(MassLynxScanItem, String)>> {
+        self.check_open()?;
+        let f = self
+            .functions
+            .get(which_function)
+            .ok_or_else(|| function_out_of_range(which_function, self.functions.len()))?;
+        let names = self.info_reader.get_scan_item_names(&f.scan_items)?;
+        Ok(f.scan_items
+            .iter()
+            .map(|item| (*item, names.get(*item).unwrap_or_default()))
+            .collect())
+    }
+
     pub fn read_scan_items(
         &mut self,
         which_function: usize,
         scan: usize,
     ) -> MassLynxResult<Vec<(MassLynxScanItem, String)>> {
+        self.check_open()?;
         if let Some(f) = self.functions.get(which_function) {
-            let params_values = self
-                .info_reader
-                .get_scan_item_values_for_scan(which_function, scan, &f.scan_items)
+            self.parameters.scan_items.clear_keys(&f.scan_items)?;
+            let params = &self.parameters.scan_items;
+            self.info_reader
+                .get_scan_item_values_for_scan_into(which_function, scan, &f.scan_items, params)
                 .map_err(|e| self.augment_function_error(e))?;
-            let items: Vec<_> = params_values.iter::<MassLynxScanItem>().collect();
+            let items: Vec<_> = f
+                .scan_items
+                .iter()
+                .filter_map(|item| params.get(*item).ok().map(|v| (*item, v)))
+                .filter(|(_, v)| !v.is_empty())
+                .collect();
             Ok(items)
         } else {
             Ok(Vec::new())
         }
     }
 
+    /// Read the spectrum at `index`, returning `None` if it is out of bounds or could not be read.
+    ///
+    /// See [`MassLynxReader::try_get_spectrum`] to learn why a spectrum could not be read.
     pub fn get_spectrum(&mut self, index: usize) -> Option<Spectrum> {
-        let entry = *self.spectrum_index.get(index)?;
+        self.try_get_spectrum(index).ok()
+    }
+
+    /// Read the spectrum at `index`, preserving the error if it is out of bounds or could
+    /// not be read.
+    pub fn try_get_spectrum(&mut self, index: usize) -> MassLynxResult<Spectrum> {
+        self.check_open()?;
+        let entry = *self
+            .spectrum_index
+            .get(index)
+            .ok_or_else(|| index_out_of_bounds("spectrum", index, self.spectrum_index.len()))?;
 
         let time = self
             .info_reader
-            .get_retention_time(entry.function, entry.cycle)
-            .ok()?;
+            .get_retention_time(entry.function, entry.cycle)?;
 
-        let ion_mode = self.info_reader.get_ion_mode(entry.function).ok()?;
-        let is_continuum = self.info_reader.is_continuum(entry.function).ok()?;
+        let ion_mode = self.info_reader.get_ion_mode(entry.function)?;
+        let is_continuum = self.info_reader.is_continuum(entry.function)?;
 
-        let items = self.read_scan_items(entry.function, entry.cycle).ok()?;
+        let items = self.read_scan_items(entry.function, entry.cycle)?;
+        let centroided = is_continuum && self.scan_reading_options.centroid();
 
         let spec = match entry.drift_index {
             Some(i) => {
                 let (mzs, intens) = if self.scan_reading_options.load_signal {
-                    self.scan_reader
-                        .read_drift_scan(entry.function, entry.cycle, i as usize)
-                        .ok()?
+                    let (mzs, intens) = self.scan_reader.read_drift_scan(
+                        entry.function,
+                        entry.cycle,
+                        i as usize,
+                    )?;
+                    self.centroid_if_needed(is_continuum, mzs, intens)?
                 } else {
                     (Vec::new(), Vec::new())
                 };
 
-                let drift_time = self.info_reader.get_drift_time(i as usize).ok();
+                let drift_time = self
+                    .drift_time_axis(entry.function)
+                    .ok()
+                    .and_then(|axis| axis.get(i as usize).copied());
 
                 Spectrum::new(
                     mzs,
@@ -515,15 +1467,14 @@ impl MassLynxReader {
                     entry,
                     drift_time,
                     ion_mode,
-                    is_continuum,
+                    is_continuum && !centroided,
                     items,
                 )
             }
             None => {
                 let (mzs, intens) = if self.scan_reading_options.load_signal {
-                    self.scan_reader
-                        .read_scan(entry.function, entry.cycle)
-                        .ok()?
+                    let (mzs, intens) = self.scan_reader.read_scan(entry.function, entry.cycle)?;
+                    self.centroid_if_needed(is_continuum, mzs, intens)?
                 } else {
                     Default::default()
                 };
@@ -536,42 +1487,70 @@ impl MassLynxReader {
                     entry,
                     None,
                     ion_mode,
-                    is_continuum,
+                    is_continuum && !centroided,
                     items,
                 )
             }
         };
 
-        Some(spec)
+        Ok(spec)
+    }
+
+    /// Read the spectrum whose native ID is `id`, see [`MassLynxReader::spectrum_index_of_id`]
+    pub fn get_spectrum_by_id(&mut self, id: &str) -> Option<Spectrum> {
+        let index = self.spectrum_index_of_id(id)?;
+        self.get_spectrum(index)
     }
 
     pub fn iter_spectra(&mut self) -> impl Iterator<Item = Spectrum> + '_ {
         (0..(self.len())).flat_map(|i| self.get_spectrum(i))
     }
 
+    /// Read the cycle at `index`, returning `None` if it is out of bounds, could not be read,
+    /// or belongs to the lock mass function while lock mass skipping is enabled.
+    ///
+    /// See [`MassLynxReader::try_get_cycle`] to learn why a cycle could not be read.
     pub fn get_cycle(&mut self, index: usize) -> Option<Cycle> {
-        let entry = *self.cycle_index.get(index)?;
+        self.try_get_cycle(index).ok()
+    }
+
+    /// Read the cycle at `index`, preserving the error if it is out of bounds, could not
+    /// be read, or belongs to the lock mass function while lock mass skipping is enabled.
+    pub fn try_get_cycle(&mut self, index: usize) -> MassLynxResult<Cycle> {
+        self.check_open()?;
+        let entry = *self
+            .cycle_index
+            .get(index)
+            .ok_or_else(|| index_out_of_bounds("cycle", index, self.cycle_index.len()))?;
 
         if self.scan_reading_options.skip_lockmass && self.functions[entry.function].is_lockmass {
-            return None;
+            return Err(MassLynxError::extended_new(
+                MassLynxErrorKind::SkippedLockMass,
+                SKIPPED_LOCKMASS_ERROR_CODE,
+                format!(
+                    "Cycle {index} belongs to the lock mass function {}",
+                    entry.function
+                ),
+                Some("Lock mass skipping is enabled".to_string()),
+            ));
         }
 
         let time = self
             .info_reader
-            .get_retention_time(entry.function, entry.block)
-            .ok()?;
+            .get_retention_time(entry.function, entry.block)?;
 
-        let ion_mode = self.info_reader.get_ion_mode(entry.function).ok()?;
-        let is_continuum = self.info_reader.is_continuum(entry.function).ok()?;
+        let ion_mode = self.info_reader.get_ion_mode(entry.function)?;
+        let is_continuum = self.info_reader.is_continuum(entry.function)?;
 
         let scans = if self.scan_reading_options.load_signal {
+            self.fill_drift_time_axis(entry.im_block_size)?;
             let mut scans = Vec::with_capacity(entry.im_block_size);
             for i in 0..entry.im_block_size {
-                let (mzs, intensities) = self
-                    .scan_reader
-                    .read_drift_scan(entry.function, entry.block, i)
-                    .ok()?;
-                let drift_time = self.info_reader.get_drift_time(i).ok()?;
+                let (mzs, intensities) =
+                    self.scan_reader
+                        .read_drift_scan(entry.function, entry.block, i)?;
+                let (mzs, intensities) = self.centroid_if_needed(is_continuum, mzs, intensities)?;
+                let drift_time = self.drift_time_axis[i];
                 scans.push(DriftScan::new(drift_time, mzs, intensities));
             }
             scans
@@ -579,23 +1558,93 @@ impl MassLynxReader {
             Vec::new()
         };
 
-        let items = self.read_scan_items(entry.function, entry.block).ok()?;
+        let items = self.read_scan_items(entry.function, entry.block)?;
+        let centroided = is_continuum && self.scan_reading_options.centroid();
 
-        Some(Cycle::new(
+        Ok(Cycle::new(
             scans,
             index,
             entry,
             time,
             ion_mode,
-            is_continuum,
+            is_continuum && !centroided,
             items,
         ))
     }
 
+    /// Read the cycle whose native ID is `id`, see [`MassLynxReader::cycle_index_of_id`]
+    pub fn get_cycle_by_id(&mut self, id: &str) -> Option<Cycle> {
+        let index = self.cycle_index_of_id(id)?;
+        self.get_cycle(index)
+    }
+
     pub fn iter_cycles(&mut self) -> impl Iterator<Item = Cycle> + '_ {
         (0..(self.cycle_index.len())).flat_map(|i| self.get_cycle(i))
     }
 
+    /// Read every cycle, consuming the reader, as with [`MassLynxReader::iter_cycles`]
+    pub fn into_cycles(self) -> IntoIter<Cycle> {
+        IntoIter {
+            len: self.cycle_index.len(),
+            reader: self,
+            index: 0,
+            read: Self::get_cycle,
+        }
+    }
+
+    /// Read every spectrum on a background thread with its own copy of this reader, keeping
+    /// up to `depth` spectra ready ahead of the consumer.
+    ///
+    /// See [`MassLynxReader::try_clone`] for what is copied. Spectra that could not be read are
+    /// skipped, as with [`MassLynxReader::iter_spectra`].
+    pub fn iter_spectra_prefetch(&self, depth: usize) -> MassLynxResult<PrefetchIter<Spectrum>> {
+        self.prefetch_with(self.len(), depth, Self::get_spectrum)
+    }
+
+    /// Read every cycle on a background thread, see [`MassLynxReader::iter_spectra_prefetch`]
+    pub fn iter_cycles_prefetch(&self, depth: usize) -> MassLynxResult<PrefetchIter<Cycle>> {
+        self.prefetch_with(self.cycle_index.len(), depth, Self::get_cycle)
+    }
+
+    fn prefetch_with<T: Send + 'static>(
+        &self,
+        len: usize,
+        depth: usize,
+        read: fn(&mut MassLynxReader, usize) -> Option<T>,
+    ) -> MassLynxResult<PrefetchIter<T>> {
+        let template = self.template();
+        let (opened_tx, opened_rx) = mpsc::channel();
+        let (sender, receiver) = mpsc::sync_channel(depth.max(1));
+
+        thread::spawn(move || {
+            let mut reader = match template.open() {
+                Ok(reader) => {
+                    let _ = opened_tx.send(Ok(()));
+                    reader
+                }
+                Err(e) => {
+                    let _ = opened_tx.send(Err(e));
+                    return;
+                }
+            };
+            for item in (0..len).filter_map(|i| read(&mut reader, i)) {
+                if sender.send(item).is_err() {
+                    break;
+                }
+            }
+        });
+
+        match opened_rx.recv() {
+            Ok(Ok(())) => Ok(PrefetchIter { receiver }),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(MassLynxError::new(
+                MassLynxErrorKind::Thread,
+                PREFETCH_THREAD_ERROR_CODE,
+                "The prefetch thread exited before opening the run".to_string(),
+            )),
+        }
+    }
+
     pub fn get_signal_loading(&self) -> bool {
         self.scan_reading_options.load_signal()
     }
@@ -604,6 +1653,45 @@ impl MassLynxReader {
         self.scan_reading_options.set_load_signal(load_signal)
     }
 
+    /// Whether profile spectra are centroided with the SDK's centroider when they are read
+    pub fn get_centroiding(&self) -> bool {
+        self.scan_reading_options.centroid()
+    }
+
+    /// Set whether profile spectra, and the drift scans of profile cycles, should be centroided
+    /// with the SDK's centroider when they are read. Spectra that were centroided are marked as
+    /// not being continuum.
+    pub fn set_centroiding(&mut self, centroid: bool) {
+        self.scan_reading_options.set_centroid(centroid)
+    }
+
+    fn centroid_if_needed(
+        &mut self,
+        is_continuum: bool,
+        mzs: Vec<f32>,
+        intensities: Vec<f32>,
+    ) -> MassLynxResult<(Vec<f32>, Vec<f32>)> {
+        if !is_continuum || !self.scan_reading_options.centroid() || mzs.is_empty() {
+            return Ok((mzs, intensities));
+        }
+
+        let processor = match self.scan_processor.as_mut() {
+            Some(processor) => processor,
+            None => {
+                let mut processor = MassLynxScanProcessor::new()?;
+                processor.set_raw_data_from_reader(&self.scan_reader)?;
+                self.scan_processor.insert(processor)
+            }
+        };
+
+        processor.set_scan(&mzs, &intensities)?;
+        processor.centroid()?;
+        let mut centroid_mzs = Vec::new();
+        let mut centroid_intensities = Vec::new();
+        processor.get(&mut centroid_mzs, &mut centroid_intensities)?;
+        Ok((centroid_mzs, centroid_intensities))
+    }
+
     pub fn get_lockmass_skipping(&self) -> bool {
         self.scan_reading_options.skip_lockmass()
     }
@@ -616,6 +1704,7 @@ impl MassLynxReader {
 /// Read chromatograms and mobilograms
 impl MassLynxReader {
     pub fn tic_of(&mut self, which_function: usize) -> MassLynxResult<(Vec<f32>, Vec<f32>)> {
+        self.check_open()?;
         let mut times = Vec::new();
         let mut intensities = Vec::new();
         self.chromatogram_reader
@@ -626,6 +1715,7 @@ impl MassLynxReader {
     }
 
     pub fn bpi_of(&mut self, which_function: usize) -> MassLynxResult<(Vec<f32>, Vec<f32>)> {
+        self.check_open()?;
         let mut times = Vec::new();
         let mut intensities = Vec::new();
         self.chromatogram_reader
@@ -635,11 +1725,19 @@ impl MassLynxReader {
         Ok((times, intensities))
     }
 
+    /// Read the TIC of the whole run, merging the TIC of each function.
+    ///
+    /// The merged chromatogram is cached, see [`MassLynxReader::refresh_chromatograms`].
     pub fn tic(&mut self) -> MassLynxResult<(Vec<f32>, Vec<f32>)> {
+        if let Some(chrom) = self.chromatogram_cache.tic.as_ref() {
+            return Ok(chrom.clone());
+        }
+
         let mut chrom_slices: Vec<
             std::iter::Peekable<std::iter::Zip<std::vec::IntoIter<f32>, std::vec::IntoIter<f32>>>,
         > = Vec::new();
 
+        self.check_open()?;
         for f in 0..self.info_reader.function_count()? {
             let mut times_of = Vec::new();
             let mut intensities_of = Vec::new();
@@ -655,14 +1753,24 @@ impl MassLynxReader {
             );
         }
 
-        Ok(ChromatogramMerger::new(chrom_slices).merge())
+        let chrom = ChromatogramMerger::new(chrom_slices).merge();
+        self.chromatogram_cache.tic = Some(chrom.clone());
+        Ok(chrom)
     }
 
+    /// Read the BPI of the whole run, merging the BPI of each function.
+    ///
+    /// The merged chromatogram is cached, see [`MassLynxReader::refresh_chromatograms`].
     pub fn bpi(&mut self) -> MassLynxResult<(Vec<f32>, Vec<f32>)> {
+        if let Some(chrom) = self.chromatogram_cache.bpi.as_ref() {
+            return Ok(chrom.clone());
+        }
+
         let mut chrom_slices: Vec<
             std::iter::Peekable<std::iter::Zip<std::vec::IntoIter<f32>, std::vec::IntoIter<f32>>>,
         > = Vec::new();
 
+        self.check_open()?;
         for f in 0..self.info_reader.function_count()? {
             let mut times_of = Vec::new();
             let mut intensities_of = Vec::new();
@@ -678,7 +1786,15 @@ impl MassLynxReader {
             );
         }
 
-        Ok(ChromatogramMerger::new(chrom_slices).merge())
+        let chrom = ChromatogramMerger::new(chrom_slices).merge();
+        self.chromatogram_cache.bpi = Some(chrom.clone());
+        Ok(chrom)
+    }
+
+    /// Discard the cached whole-run chromatograms so that they are read again from the
+    /// SDK on their next use.
+    pub fn refresh_chromatograms(&mut self) {
+        self.chromatogram_cache.clear();
     }
 
     pub fn read_xic(
@@ -688,6 +1804,7 @@ impl MassLynxReader {
         mass_window: f32,
         daughters: bool,
     ) -> MassLynxResult<(Vec<f32>, Vec<f32>)> {
+        self.check_open()?;
         let mut time_array = Vec::new();
         let mut intensity_array = Vec::new();
 
@@ -712,6 +1829,7 @@ impl MassLynxReader {
         mass_window: f32,
         daughters: bool,
     ) -> MassLynxResult<Vec<(Arc<Vec<f32>>, Vec<f32>)>> {
+        self.check_open()?;
         let mut time_array = Vec::new();
         let mut intensity_arrays: Vec<_> = (0..(masses.len())).map(|_| Vec::new()).collect();
 
@@ -743,6 +1861,40 @@ impl MassLynxReader {
         start_mass: f32,
         end_mass: f32,
     ) -> MassLynxResult<(Vec<f32>, Vec<f32>)> {
+        let (drift_bins, intensity_array) =
+            self.read_mobilogram_bins(which_function, start_scan, end_scan, start_mass, end_mass)?;
+        let drift_bins = drift_bins
+            .into_iter()
+            .map(|i| {
+                usize::try_from(i).map_err(|_| {
+                    MassLynxError::new(
+                        MassLynxErrorKind::Sdk,
+                        INVALID_SDK_VALUE_ERROR_CODE,
+                        format!("The mobilogram of function {which_function} has drift bin {i}"),
+                    )
+                })
+            })
+            .collect::<MassLynxResult<Vec<usize>>>()?;
+        let n_bins = drift_bins.iter().map(|i| i + 1).max().unwrap_or_default();
+        self.fill_drift_time_axis(n_bins)?;
+        let drift_times = drift_bins
+            .into_iter()
+            .map(|i| self.drift_time_axis[i] as f32)
+            .collect();
+        Ok((drift_times, intensity_array))
+    }
+
+    /// Read a mobilogram like [`MassLynxReader::read_mobilogram`], but keep the drift bin
+    /// indices instead of converting them to drift times.
+    pub fn read_mobilogram_bins(
+        &mut self,
+        which_function: usize,
+        start_scan: usize,
+        end_scan: usize,
+        start_mass: f32,
+        end_mass: f32,
+    ) -> MassLynxResult<(Vec<i32>, Vec<f32>)> {
+        self.check_open()?;
         let mut drift_bins = Vec::new();
         let mut intensity_array = Vec::new();
         self.chromatogram_reader
@@ -756,15 +1908,145 @@ impl MassLynxReader {
                 &mut intensity_array,
             )
             .map_err(|e| self.augment_function_error(e))?;
-        let drift_times: MassLynxResult<Vec<f32>> = drift_bins
-            .into_iter()
-            .map(|i| {
-                self.info_reader
-                    .get_drift_time(i as usize)
-                    .map(|f| f as f32)
-            })
-            .collect();
-        Ok((drift_times?, intensity_array))
+        Ok((drift_bins, intensity_array))
+    }
+
+    /// Get the drift time of a drift bin
+    pub fn drift_time_of(&mut self, drift_bin: usize) -> MassLynxResult<f64> {
+        self.check_open()?;
+        if let Some(drift_time) = self.drift_time_axis.get(drift_bin) {
+            return Ok(*drift_time);
+        }
+        self.info_reader.get_drift_time(drift_bin)
+    }
+
+    /// Get the drift time of every drift bin of `which_function`, in milliseconds.
+    ///
+    /// The drift times are read from the SDK once and cached, so later calls and reading
+    /// cycles and mobilograms do not query them again. Functions without ion mobility have
+    /// an empty axis.
+    pub fn drift_time_axis(&mut self, which_function: usize) -> MassLynxResult<&[f64]> {
+        let n_bins = self
+            .functions
+            .get(which_function)
+            .map(|f| f.ion_mobility_block_size)
+            .ok_or_else(|| function_out_of_range(which_function, self.functions.len()))?;
+        self.fill_drift_time_axis(n_bins)?;
+        Ok(&self.drift_time_axis[..n_bins])
+    }
+
+    /// Make sure the cached drift time axis covers the first `n_bins` drift bins
+    fn fill_drift_time_axis(&mut self, n_bins: usize) -> MassLynxResult<()> {
+        if self.drift_time_axis.len() >= n_bins {
+            return Ok(());
+        }
+        self.check_open()?;
+        for i in self.drift_time_axis.len()..n_bins {
+            let drift_time = self.info_reader.get_drift_time(i)?;
+            self.drift_time_axis.push(drift_time);
+        }
+        Ok(())
+    }
+
+    /// Convert a drift time to a collisional cross section for an ion of the given m/z and charge
+    pub fn collisional_cross_section(
+        &self,
+        drift_time: f32,
+        mz: f32,
+        charge: i32,
+    ) -> MassLynxResult<f64> {
+        self.check_open()?;
+        self.info_reader
+            .get_collisional_cross_section(drift_time, mz, charge)
+    }
+
+    /// Convert a collisional cross section to a drift time for an ion of the given m/z and charge
+    pub fn drift_time_for_ccs(&self, ccs: f32, mz: f32, charge: i32) -> MassLynxResult<f64> {
+        self.check_open()?;
+        self.info_reader.get_drift_time_for_ccs(ccs, mz, charge)
+    }
+
+    /// Describe the run's mobility calibration, from the `mob_cal.csv` file MassLynx writes
+    /// into the RAW directory when a run is CCS calibrated.
+    ///
+    /// The layout of the file is not published, so its rows are returned as they are. The SDK
+    /// does not report whether it has a usable calibration either, so `is_valid` is decided by
+    /// converting a probe ion (a drift time of 5 ms, m/z 500, charge 1) to a CCS value and
+    /// checking that the result is a positive, finite number.
+    pub fn mobility_calibration(&self) -> MassLynxResult<MobilityCalibration> {
+        self.check_open()?;
+        let read_error = |e: io::Error| {
+            MassLynxError::new(
+                MassLynxErrorKind::Io,
+                IO_ERROR_CODE,
+                format!("Failed to read the mobility calibration: {e}"),
+            )
+        };
+        let path = fs::read_dir(self.path())
+            .map_err(read_error)?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|p| {
+                p.file_name()
+                    .is_some_and(|name| name.eq_ignore_ascii_case("mob_cal.csv"))
+            });
+        let Some(path) = path else {
+            return Ok(MobilityCalibration::default());
+        };
+
+        let modified = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(|t| DateTime::<Utc>::from(t).naive_utc());
+        let content = fs::read(&path).map_err(read_error)?;
+        let content = decode_text(&content);
+        let rows = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(content.as_bytes())
+            .records()
+            .map(|record| record.map(|r| r.iter().map(str::to_string).collect()))
+            .collect::<Result<Vec<Vec<String>>, _>>()
+            .map_err(|e| {
+                MassLynxError::new(
+                    MassLynxErrorKind::Decode,
+                    DECODE_ERROR_CODE,
+                    format!("Failed to parse {}: {e}", path.display()),
+                )
+            })?;
+        let is_valid = self
+            .collisional_cross_section(5.0, 500.0, 1)
+            .is_ok_and(|ccs| ccs.is_finite() && ccs > 0.0);
+
+        Ok(MobilityCalibration {
+            path: Some(path),
+            is_valid,
+            modified,
+            rows,
+        })
+    }
+
+    /// Convert many `(drift_time, mz, charge)` ions to collisional cross sections.
+    ///
+    /// Each ion gets its own result, in the same order, so one failed conversion does not
+    /// discard the rest.
+    pub fn collisional_cross_sections(
+        &self,
+        ions: &[(f32, f32, i32)],
+    ) -> MassLynxResult<Vec<MassLynxResult<f64>>> {
+        self.check_open()?;
+        Ok(self.info_reader.get_collisional_cross_sections(ions))
+    }
+
+    /// Convert many `(ccs, mz, charge)` ions to drift times, see
+    /// [`MassLynxReader::collisional_cross_sections`]
+    pub fn drift_times_for_ccs(
+        &self,
+        ions: &[(f32, f32, i32)],
+    ) -> MassLynxResult<Vec<MassLynxResult<f64>>> {
+        self.check_open()?;
+        Ok(self.info_reader.get_drift_times_for_ccs(ions))
     }
 
     pub fn analog_trace_count(&self) -> usize {
@@ -790,6 +2072,22 @@ impl MassLynxReader {
         })
     }
 
+    /// Get the time offset, in minutes, recorded in the acquisition header for the analog
+    /// channel at `index`. Adding it to the channel's times aligns the trace with the MS data.
+    ///
+    /// MassLynx only records offsets for the first four channels.
+    pub fn analog_time_offset(&self, index: usize) -> Option<f32> {
+        let item = match index {
+            0 => MassLynxHeaderItem::ANALOG_CH1_OFFSET,
+            1 => MassLynxHeaderItem::ANALOG_CH2_OFFSET,
+            2 => MassLynxHeaderItem::ANALOG_CH3_OFFSET,
+            3 => MassLynxHeaderItem::ANALOG_CH4_OFFSET,
+            _ => return None,
+        };
+        let params = self.info_reader.get_header_items(&[item]).ok()?;
+        params.get(item).ok()?.trim().parse().ok()
+    }
+
     pub fn get_analog_trace(&mut self, index: usize) -> Option<Trace> {
         let num_analog_traces = self
             .analog_reader
@@ -811,48 +2109,222 @@ impl MassLynxReader {
 /// General metadata reading
 impl MassLynxReader {
     pub fn read_headers_from_file(&self) -> io::Result<HashMap<String, String>> {
-        let mut headers_path = self.path().join("_header.txt");
-        let mut headers: HashMap<String, String> = HashMap::new();
+        read_header_file(self.path())
+    }
 
-        if !headers_path.exists() {
-            headers_path = self.path().join("_HEADER.TXT");
-            if !headers_path.exists() {
-                return Ok(headers);
-            }
+    pub fn header_items(&self) -> MassLynxResult<Vec<(MassLynxHeaderItem, String)>> {
+        self.check_open()?;
+        let params = &self.parameters.header_items;
+        self.info_reader
+            .get_header_items_into(MassLynxHeaderItem::ALL, params)?;
+        let header_items: Vec<(MassLynxHeaderItem, String)> = MassLynxHeaderItem::iter()
+            .filter_map(|item| params.get(item).ok().map(|v| (item, v)))
+            .filter(|(_, v)| !v.is_empty())
+            .collect();
+        Ok(header_items)
+    }
+
+    /// Merge the header items read through the SDK with those in the `_HEADER.TXT` file,
+    /// pairing the two by name.
+    ///
+    /// SDK items come first in item order, followed by any keys only found in the file.
+    pub fn merged_headers(&self) -> MassLynxResult<Vec<HeaderEntry>> {
+        let mut file_headers = self.read_headers_from_file().map_err(|e| {
+            MassLynxError::new(
+                MassLynxErrorKind::Io,
+                IO_ERROR_CODE,
+                format!("Failed to read the header file: {e}"),
+            )
+        })?;
+
+        let mut entries = Vec::new();
+        for (item, value) in self.header_items()? {
+            let key = file_headers
+                .keys()
+                .find(|k| header_file_key_matches(item, k))
+                .cloned();
+            let file_value = key.as_ref().and_then(|k| file_headers.remove(k));
+            entries.push(HeaderEntry {
+                key: key.unwrap_or_else(|| format!("{item:?}")),
+                item: Some(item),
+                sdk_value: Some(value),
+                file_value,
+            });
         }
 
-        let handle = io::BufReader::new(fs::File::open(headers_path)?);
+        let mut file_only: Vec<_> = file_headers.into_iter().collect();
+        file_only.sort();
+        entries.extend(file_only.into_iter().map(|(key, value)| HeaderEntry {
+            key,
+            item: None,
+            sdk_value: None,
+            file_value: Some(value),
+        }));
+        Ok(entries)
+    }
 
-        for line in handle.lines().flatten() {
-            if !line.starts_with("$$ ") {
-                continue;
-            }
+    pub fn acquisition_information(
+        &mut self,
+    ) -> MassLynxResult<HashMap<AcquisitionParameter, String>> {
+        self.check_open()?;
+        Ok(self.info_reader.get_acquisition_info()?.to_hashmap())
+    }
 
-            if let Some((key, value)) = line.split_once(':') {
-                headers
-                    .entry(key[3..].trim_ascii().to_string())
-                    .insert_entry(value.trim().to_string());
+    /// Like [`MassLynxReader::acquisition_information`], but parameters this crate does not
+    /// recognize are kept with their raw key instead of being dropped
+    pub fn acquisition_information_with_unknown(
+        &mut self,
+    ) -> MassLynxResult<Vec<(ItemKey<AcquisitionParameter>, String)>> {
+        self.check_open()?;
+        Ok(self.info_reader.get_acquisition_info()?.iter().collect())
+    }
+
+    /// Get the time the run was acquired from the `ACQUIRED_DATE` and `ACQUIRED_TIME` header
+    /// items, see [`parse_acquisition_datetime`].
+    ///
+    /// Returns `None` and logs a warning if either is missing or cannot be parsed.
+    pub fn acquisition_start_time(&self) -> Option<NaiveDateTime> {
+        let items = match self.header_items() {
+            Ok(items) => items,
+            Err(e) => {
+                warn!("Failed to read header items for the acquisition start time: {e}");
+                return None;
             }
+        };
+        let date = items
+            .iter()
+            .find(|(k, _)| *k == MassLynxHeaderItem::ACQUIRED_DATE)
+            .map(|(_, v)| v.as_str())?;
+        let time = items
+            .iter()
+            .find(|(k, _)| *k == MassLynxHeaderItem::ACQUIRED_TIME)
+            .map(|(_, v)| v.as_str())
+            .unwrap_or_default();
+        let start_time = parse_acquisition_datetime(date, time);
+        if start_time.is_none() {
+            warn!("Failed to parse acquisition start time from {date:?} and {time:?}");
+        }
+        start_time
+    }
+}
+
+const ACQUISITION_DATE_FORMATS: &[&str] = &[
+    "%d-%b-%y", "%d-%b-%Y", "%d %b %Y", "%d/%m/%y", "%d/%m/%Y", "%m/%d/%Y", "%Y-%m-%d", "%d.%m.%Y",
+    "%Y/%m/%d",
+];
+
+const ACQUISITION_TIME_FORMATS: &[&str] = &["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"];
+
+/// Parse the acquisition date and time as MassLynx writes them in the `ACQUIRED_DATE` and
+/// `ACQUIRED_TIME` header items.
+///
+/// MassLynx formats these according to the acquisition PC's locale, e.g. "12-Mar-2021" and
+/// "14:22:05", so several common date and time formats are tried in turn. Ambiguous numeric
+/// dates are read as day first. An empty time falls back to midnight, as does an unparsable
+/// one after logging a warning, while an unparsable date yields `None`.
+pub fn parse_acquisition_datetime(date: &str, time: &str) -> Option<NaiveDateTime> {
+    let date = date.trim();
+    let time_text = time.trim();
+
+    let date = ACQUISITION_DATE_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(date, fmt).ok())?;
+
+    if time_text.is_empty() {
+        return Some(date.and_time(NaiveTime::default()));
+    }
+    let time = ACQUISITION_TIME_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveTime::parse_from_str(time_text, fmt).ok())
+        .unwrap_or_else(|| {
+            warn!("Failed to parse acquisition time {time_text:?}, using midnight");
+            NaiveTime::default()
+        });
+
+    Some(date.and_time(time))
+}
+
+/// Normalize a header name for comparison by dropping case and anything but letters and digits
+fn normalize_header_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Read the `$$ key: value` lines of the `_HEADER.TXT` file in the RAW directory `root`.
+/// Returns no headers if the file is missing.
+pub(crate) fn read_header_file(root: &Path) -> io::Result<HashMap<String, String>> {
+    let mut headers_path = root.join("_header.txt");
+    let mut headers: HashMap<String, String> = HashMap::new();
+
+    if !headers_path.exists() {
+        headers_path = root.join("_HEADER.TXT");
+        if !headers_path.exists() {
+            return Ok(headers);
+        }
+    }
+
+    let content = fs::read(headers_path)?;
+
+    for line in decode_text(&content).lines() {
+        if !line.starts_with("$$ ") {
+            continue;
         }
 
-        Ok(headers)
+        if let Some((key, value)) = line.split_once(':') {
+            headers
+                .entry(key[3..].trim_ascii().to_string())
+                .insert_entry(value.trim().to_string());
+        }
     }
 
-    pub fn header_items(&self) -> MassLynxResult<Vec<(MassLynxHeaderItem, String)>> {
-        let items: Vec<_> = MassLynxHeaderItem::iter().collect();
-        let items = self.info_reader.get_header_items(&items)?;
-        let header_items: Vec<(MassLynxHeaderItem, String)> =
-            items.iter().filter(|(_, v)| !v.is_empty()).collect();
-        Ok(header_items)
+    Ok(headers)
+}
+
+/// Check if a `_HEADER.TXT` key, like "Acquired Name", names the same thing as `item`
+pub(crate) fn header_file_key_matches(item: MassLynxHeaderItem, key: &str) -> bool {
+    let key = normalize_header_key(key);
+    match item {
+        MassLynxHeaderItem::LAB_NAME => key == "laboratoryname" || key == "labname",
+        _ => key == normalize_header_key(&format!("{item:?}")),
     }
+}
 
-    pub fn acquisition_information(
-        &mut self,
-    ) -> MassLynxResult<HashMap<AcquisitionParameter, String>> {
-        Ok(self.info_reader.get_acquisition_info()?.to_hashmap())
+/// A header value as reported by the SDK and by the `_HEADER.TXT` file
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeaderEntry {
+    pub key: String,
+    pub item: Option<MassLynxHeaderItem>,
+    pub sdk_value: Option<String>,
+    pub file_value: Option<String>,
+}
+
+impl HeaderEntry {
+    /// Check if the SDK and the header file both report this value and disagree on it
+    pub fn is_conflicting(&self) -> bool {
+        match (self.sdk_value.as_ref(), self.file_value.as_ref()) {
+            (Some(sdk), Some(file)) => sdk.trim() != file.trim(),
+            _ => false,
+        }
     }
 }
 
+/// A run's mobility (CCS) calibration, see [`MassLynxReader::mobility_calibration`]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MobilityCalibration {
+    /// The calibration file, `None` if the run is not calibrated
+    pub path: Option<PathBuf>,
+    /// Whether the SDK converts a probe ion to a plausible CCS value with this calibration
+    pub is_valid: bool,
+    /// When the calibration file was last modified
+    pub modified: Option<NaiveDateTime>,
+    /// The rows of the calibration file, split into comma-separated fields
+    pub rows: Vec<Vec<String>>,
+}
+
 struct ChromatogramMerger {
     iters:
         Vec<std::iter::Peekable<std::iter::Zip<std::vec::IntoIter<f32>, std::vec::IntoIter<f32>>>>,
@@ -895,6 +2367,7 @@ impl ChromatogramMerger {
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spectrum {
     pub mz_array: Vec<f32>,
     pub intensity_array: Vec<f32>,
@@ -939,9 +2412,51 @@ impl Spectrum {
     pub fn native_id(&self) -> String {
         self.identifier.native_id()
     }
+
+    pub fn polarity(&self) -> Polarity {
+        self.ion_mode.polarity()
+    }
+
+    /// The value of the scan item `item`, if present and not blank
+    pub fn scan_item(&self, item: MassLynxScanItem) -> Option<&str> {
+        find_scan_item(&self.items, item)
+    }
+
+    /// The value of the scan item `item` as a number, see [`parse_scan_item_number`]
+    pub fn scan_item_number(&self, item: MassLynxScanItem) -> Option<f64> {
+        parse_scan_item_number(item, self.scan_item(item)?)
+    }
+}
+
+fn find_scan_item(items: &[(MassLynxScanItem, String)], item: MassLynxScanItem) -> Option<&str> {
+    items
+        .iter()
+        .find(|(k, _)| *k == item)
+        .map(|(_, v)| v.trim())
+        .filter(|v| !v.is_empty())
+}
+
+/// Parse the value of the scan item `item` as a number.
+///
+/// Blank values, placeholders like `N/A` and anything else that is not a finite number give
+/// `None` rather than an error, and are logged at debug level. The raw text is still available
+/// from the item list.
+pub fn parse_scan_item_number(item: MassLynxScanItem, value: &str) -> Option<f64> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() => Some(number),
+        _ => {
+            debug!("Scan item {item:?} has a non-numeric value {value:?}");
+            None
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DriftScan {
     pub drift_time: f64,
     pub mz_array: Vec<f32>,
@@ -959,6 +2474,7 @@ impl DriftScan {
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cycle {
     pub signal: Vec<DriftScan>,
     pub index: usize,
@@ -997,9 +2513,24 @@ impl Cycle {
     pub fn native_id(&self) -> String {
         self.identifier.native_id()
     }
+
+    pub fn polarity(&self) -> Polarity {
+        self.ion_mode.polarity()
+    }
+
+    /// The value of the scan item `item`, if present and not blank
+    pub fn scan_item(&self, item: MassLynxScanItem) -> Option<&str> {
+        find_scan_item(&self.items, item)
+    }
+
+    /// The value of the scan item `item` as a number, see [`parse_scan_item_number`]
+    pub fn scan_item_number(&self, item: MassLynxScanItem) -> Option<f64> {
+        parse_scan_item_number(item, self.scan_item(item)?)
+    }
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Trace {
     pub name: String,
     pub unit: String,
@@ -1017,3 +2548,86 @@ impl Trace {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scan_item_number() {
+        let item = MassLynxScanItem::SET_MASS;
+        assert_eq!(parse_scan_item_number(item, "12.5"), Some(12.5));
+        assert_eq!(parse_scan_item_number(item, " 12.5 "), Some(12.5));
+        for value in ["", "  ", "N/A", "NaN", "inf", "-inf"] {
+            assert_eq!(parse_scan_item_number(item, value), None, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_acquisition_date_formats() {
+        let expected = NaiveDate::from_ymd_opt(2021, 3, 25).unwrap();
+        let cases = [
+            ("%d-%b-%y", "25-Mar-21"),
+            ("%d-%b-%Y", "25-Mar-2021"),
+            ("%d %b %Y", "25 Mar 2021"),
+            ("%d/%m/%y", "25/03/21"),
+            ("%d/%m/%Y", "25/03/2021"),
+            ("%m/%d/%Y", "03/25/2021"),
+            ("%Y-%m-%d", "2021-03-25"),
+            ("%d.%m.%Y", "25.03.2021"),
+            ("%Y/%m/%d", "2021/03/25"),
+        ];
+        assert_eq!(
+            cases.iter().map(|(fmt, _)| *fmt).collect::<Vec<_>>(),
+            ACQUISITION_DATE_FORMATS
+        );
+        for (fmt, date) in cases {
+            let parsed = parse_acquisition_datetime(date, "14:22:05");
+            assert_eq!(
+                parsed,
+                Some(expected.and_hms_opt(14, 22, 5).unwrap()),
+                "{fmt}: {date}"
+            );
+        }
+        assert_eq!(
+            parse_acquisition_datetime("12-Mar-2021", ""),
+            NaiveDate::from_ymd_opt(2021, 3, 12)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+        );
+        assert_eq!(
+            parse_acquisition_datetime("03/04/2021", ""),
+            NaiveDate::from_ymd_opt(2021, 4, 3)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+        );
+        assert_eq!(parse_acquisition_datetime("Mar 2021", "14:22:05"), None);
+        assert_eq!(parse_acquisition_datetime("", "14:22:05"), None);
+    }
+
+    #[test]
+    fn test_parse_acquisition_time_formats() {
+        let date = NaiveDate::from_ymd_opt(2021, 3, 12).unwrap();
+        let cases = [
+            ("%H:%M:%S", "14:22:05", date.and_hms_opt(14, 22, 5)),
+            ("%H:%M", "14:22", date.and_hms_opt(14, 22, 0)),
+            ("%I:%M:%S %p", "2:22:05 PM", date.and_hms_opt(14, 22, 5)),
+            ("%I:%M %p", "2:22 PM", date.and_hms_opt(14, 22, 0)),
+        ];
+        assert_eq!(
+            cases.iter().map(|(fmt, _, _)| *fmt).collect::<Vec<_>>(),
+            ACQUISITION_TIME_FORMATS
+        );
+        for (fmt, time, expected) in cases {
+            assert_eq!(
+                parse_acquisition_datetime("12-Mar-2021", time),
+                expected,
+                "{fmt}: {time}"
+            );
+        }
+        assert_eq!(
+            parse_acquisition_datetime("12-Mar-2021", "not a time"),
+            date.and_hms_opt(0, 0, 0)
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Follow a run while it is being acquired.
+//!
+//! [`MassLynxReader::subscribe`] starts a background thread that opens its own reader on the
+//! same RAW directory, polls it with [`MassLynxReader::refresh`] and reports what changed as
+//! [`AcquisitionEvent`]s.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::base::WATCHER_THREAD_ERROR_CODE;
+use crate::reader::{CycleIndexEntry, MassLynxReader};
+use crate::{MassLynxError, MassLynxErrorKind, MassLynxResult};
+
+/// Something that happened in a run being acquired
+#[derive(Debug)]
+pub enum AcquisitionEvent {
+    /// A cycle was written. Its `index` is its position in the watcher's own cycle index,
+    /// which may differ from the subscribing reader's until that reader is refreshed.
+    NewCycle(CycleIndexEntry),
+    /// The acquisition passed the end time the method set for this function
+    FunctionComplete(usize),
+    /// Every function is complete, or no new cycles were seen for
+    /// [`WatchOptions::idle_limit`] polls. No more events follow.
+    AcquisitionFinished,
+    /// Refreshing the run failed. The watcher keeps polling.
+    Error(MassLynxError),
+}
+
+/// How often to poll a run, and when to give up on it
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// The time to wait between polls
+    pub interval: Duration,
+    /// Report [`AcquisitionEvent::AcquisitionFinished`] after this many consecutive polls
+    /// without new cycles. Never give up if `None`
+    pub idle_limit: Option<usize>,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            idle_limit: Some(60),
+        }
+    }
+}
+
+/// A handle on the thread started by [`MassLynxReader::subscribe`]. The thread is stopped when
+/// this is dropped.
+#[derive(Debug)]
+pub struct AcquisitionWatcher {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl AcquisitionWatcher {
+    /// Whether the thread has exited, after [`AcquisitionEvent::AcquisitionFinished`]
+    pub fn is_finished(&self) -> bool {
+        self.handle
+            .as_ref()
+            .map(|h| h.is_finished())
+            .unwrap_or(true)
+    }
+
+    /// Stop polling and wait for the thread to exit, which may take up to one
+    /// [`WatchOptions::interval`]
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for AcquisitionWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// The polling state of the watcher thread
+struct Poller {
+    reader: MassLynxReader,
+    /// The number of scans already reported for each function
+    scan_counts: Vec<usize>,
+    /// The acquisition end time of each function, in minutes
+    end_times: Vec<Option<f64>>,
+    completed: HashSet<usize>,
+    latest_time: f64,
+}
+
+impl Poller {
+    fn new(mut reader: MassLynxReader, scan_counts: Vec<usize>) -> Self {
+        let functions: Vec<usize> = reader.functions().iter().map(|f| f.function).collect();
+        let end_times = functions
+            .into_iter()
+            .map(|f| {
+                reader
+                    .acquisition_time_range(f)
+                    .ok()
+                    .map(|(_, end)| end as f64)
+            })
+            .collect();
+        let latest_time = reader
+            .cycle_index()
+            .iter()
+            .map(|e| e.time)
+            .fold(0.0, f64::max);
+        Self {
+            reader,
+            scan_counts,
+            end_times,
+            completed: HashSet::new(),
+            latest_time,
+        }
+    }
+
+    /// Report the cycles past the known scan counts, then any functions that are now complete.
+    /// Returns `true` if every function is complete.
+    fn emit<F: FnMut(AcquisitionEvent)>(&mut self, callback: &mut F) -> bool {
+        let known = &self.scan_counts;
+        let mut new_cycles: Vec<CycleIndexEntry> = self
+            .reader
+            .cycle_index()
+            .iter()
+            .filter(|e| e.block >= known.get(e.function).copied().unwrap_or_default())
+            .copied()
+            .collect();
+        new_cycles.sort_by_key(|e| e.index);
+
+        let mut grew = HashSet::new();
+        for entry in new_cycles {
+            grew.insert(entry.function);
+            self.latest_time = self.latest_time.max(entry.time);
+            callback(AcquisitionEvent::NewCycle(entry));
+        }
+        self.scan_counts = self
+            .reader
+            .functions()
+            .iter()
+            .map(|f| f.scan_count)
+            .collect();
+
+        let functions: Vec<usize> = self
+            .reader
+            .functions()
+            .iter()
+            .filter(|f| f.ms_level > 0)
+            .map(|f| f.function)
+            .collect();
+        for function in functions.iter().copied() {
+            if self.completed.contains(&function) || grew.contains(&function) {
+                continue;
+            }
+            let ended = self
+                .end_times
+                .get(function)
+                .copied()
+                .flatten()
+                .is_some_and(|end| self.latest_time >= end);
+            if ended {
+                self.completed.insert(function);
+                callback(AcquisitionEvent::FunctionComplete(function));
+            }
+        }
+        !functions.is_empty() && functions.iter().all(|f| self.completed.contains(f))
+    }
+}
+
+impl MassLynxReader {
+    /// Watch this run for newly acquired data on a background thread, calling `callback`
+    /// with each [`AcquisitionEvent`].
+    ///
+    /// The thread opens its own reader on the same RAW directory. Cycles beyond the scan counts
+    /// this reader knows are reported as [`AcquisitionEvent::NewCycle`], starting with any
+    /// already written. Call [`MassLynxReader::refresh`] on this reader to read them.
+    pub fn subscribe<F>(
+        &self,
+        options: WatchOptions,
+        mut callback: F,
+    ) -> MassLynxResult<AcquisitionWatcher>
+    where
+        F: FnMut(AcquisitionEvent) + Send + 'static,
+    {
+        let path = self.path().to_string_lossy().to_string();
+        let scan_counts: Vec<usize> = self.functions().iter().map(|f| f.scan_count).collect();
+        let stop = Arc::new(AtomicBool::new(false));
+        let (opened_tx, opened_rx) = mpsc::channel();
+
+        let thread_stop = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            let reader = match MassLynxReader::from_path(&path) {
+                Ok(reader) => {
+                    let _ = opened_tx.send(Ok(()));
+                    reader
+                }
+                Err(e) => {
+                    let _ = opened_tx.send(Err(e));
+                    return;
+                }
+            };
+            let mut poller = Poller::new(reader, scan_counts);
+            let mut idle = 0;
+            let mut finished = poller.emit(&mut callback);
+            while !finished && !thread_stop.load(Ordering::Relaxed) {
+                thread::sleep(options.interval);
+                if thread_stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                match poller.reader.refresh() {
+                    Ok(0) => {
+                        idle += 1;
+                        finished = poller.emit(&mut callback)
+                            || options.idle_limit.is_some_and(|limit| idle >= limit);
+                    }
+                    Ok(_) => {
+                        idle = 0;
+                        finished = poller.emit(&mut callback);
+                    }
+                    Err(e) => callback(AcquisitionEvent::Error(e)),
+                }
+            }
+            if finished {
+                callback(AcquisitionEvent::AcquisitionFinished);
+            }
+        });
+
+        match opened_rx.recv() {
+            Ok(Ok(())) => Ok(AcquisitionWatcher {
+                stop,
+                handle: Some(handle),
+            }),
+            Ok(Err(e)) => {
+                let _ = handle.join();
+                Err(e)
+            }
+            Err(_) => {
+                let _ = handle.join();
+                Err(MassLynxError::new(
+                    MassLynxErrorKind::Thread,
+                    WATCHER_THREAD_ERROR_CODE,
+                    "The acquisition watcher thread exited before opening the run".to_string(),
+                ))
+            }
+        }
+    }
+}